@@ -0,0 +1,206 @@
+// Copyright 2019-2022 Artem Vorotnikov and Monero Rust Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{AddressValidation, IntegratedAddressParts};
+use monero::{util::address::PaymentId, Address, AddressType, Network};
+use std::str::FromStr;
+
+/// Network of a wallet or daemon `nettype`. Regtest, i.e. `fakechain`, uses the addresses of
+/// mainnet.
+pub(crate) fn network_from_nettype(nettype: &str) -> Option<Network> {
+    match nettype {
+        "mainnet" | "fakechain" => Some(Network::Mainnet),
+        "testnet" => Some(Network::Testnet),
+        "stagenet" => Some(Network::Stagenet),
+        _ => None,
+    }
+}
+
+/// Fail unless `address` is an address of `network`.
+pub fn check_address_network(address: &Address, network: Network) -> anyhow::Result<()> {
+    if address.network != network {
+        anyhow::bail!(
+            "{} is a {:?} address, expected a {:?} address",
+            address,
+            address.network,
+            network
+        );
+    }
+
+    Ok(())
+}
+
+/// Validate `address` without a wallet-rpc, like wallet `validate_address` on a wallet of
+/// `network`, without OpenAlias. Unless `any_net_type` is set, the addresses of other networks are
+/// not valid.
+pub fn validate_address(address: &str, network: Network, any_net_type: bool) -> AddressValidation {
+    match Address::from_str(address) {
+        Ok(address) if any_net_type || address.network == network => AddressValidation {
+            valid: true,
+            integrated: matches!(address.addr_type, AddressType::Integrated(_)),
+            subaddress: address.addr_type == AddressType::SubAddress,
+            nettype: Some(address.network),
+            openalias_address: None,
+        },
+        _ => AddressValidation {
+            valid: false,
+            integrated: false,
+            subaddress: false,
+            nettype: None,
+            openalias_address: None,
+        },
+    }
+}
+
+/// Make the integrated address of a standard address and a payment id, like wallet
+/// `make_integrated_address`. Subaddresses have no integrated address.
+pub fn integrated_address(
+    standard_address: &Address,
+    payment_id: PaymentId,
+) -> anyhow::Result<Address> {
+    if standard_address.addr_type != AddressType::Standard {
+        anyhow::bail!("{} is not a standard address", standard_address);
+    }
+
+    Ok(Address::integrated(
+        standard_address.network,
+        standard_address.public_spend,
+        standard_address.public_view,
+        payment_id,
+    ))
+}
+
+/// Split an integrated address into its standard address and payment id, like wallet
+/// `split_integrated_address`.
+pub fn split_integrated_address(
+    integrated_address: &Address,
+) -> anyhow::Result<IntegratedAddressParts> {
+    match integrated_address.addr_type {
+        AddressType::Integrated(payment_id) => Ok(IntegratedAddressParts {
+            standard_address: Address::standard(
+                integrated_address.network,
+                integrated_address.public_spend,
+                integrated_address.public_view,
+            ),
+            payment_id,
+            is_subaddress: false,
+        }),
+        _ => anyhow::bail!("{} is not an integrated address", integrated_address),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_key_pair;
+    use monero::{cryptonote::subaddress, ViewPair};
+
+    #[test]
+    fn integrated_address_round_trip() {
+        let key_pair = test_key_pair(1);
+        let address = Address::from_keypair(Network::Stagenet, &key_pair);
+        let payment_id = PaymentId::from_low_u64_be(42);
+
+        let integrated = integrated_address(&address, payment_id).unwrap();
+        assert_eq!(integrated.network, Network::Stagenet);
+        assert_eq!(integrated.addr_type, AddressType::Integrated(payment_id));
+        assert_eq!(
+            split_integrated_address(&integrated).unwrap(),
+            IntegratedAddressParts {
+                standard_address: address,
+                payment_id,
+                is_subaddress: false,
+            }
+        );
+
+        // neither from a subaddress nor from an integrated address
+        let subaddress = subaddress::get_subaddress(
+            &ViewPair::from(&key_pair),
+            subaddress::Index { major: 0, minor: 1 },
+            Some(Network::Stagenet),
+        );
+        assert!(integrated_address(&subaddress, payment_id).is_err());
+        assert!(integrated_address(&integrated, payment_id).is_err());
+        assert_eq!(
+            split_integrated_address(&address).unwrap_err().to_string(),
+            format!("{} is not an integrated address", address)
+        );
+    }
+
+    #[test]
+    fn validate_address_networks() {
+        let key_pair = test_key_pair(1);
+        let address = Address::from_keypair(Network::Stagenet, &key_pair);
+        let subaddress = subaddress::get_subaddress(
+            &ViewPair::from(&key_pair),
+            subaddress::Index { major: 0, minor: 1 },
+            Some(Network::Stagenet),
+        );
+        let integrated = integrated_address(&address, PaymentId::from_low_u64_be(42)).unwrap();
+
+        let valid = |integrated, subaddress| AddressValidation {
+            valid: true,
+            integrated,
+            subaddress,
+            nettype: Some(Network::Stagenet),
+            openalias_address: None,
+        };
+        let invalid = AddressValidation {
+            valid: false,
+            integrated: false,
+            subaddress: false,
+            nettype: None,
+            openalias_address: None,
+        };
+        let validate = |address: &Address, network, any_net_type| {
+            validate_address(&address.to_string(), network, any_net_type)
+        };
+        assert_eq!(
+            validate(&address, Network::Stagenet, false),
+            valid(false, false)
+        );
+        assert_eq!(
+            validate(&subaddress, Network::Stagenet, false),
+            valid(false, true)
+        );
+        assert_eq!(
+            validate(&integrated, Network::Stagenet, false),
+            valid(true, false)
+        );
+
+        // addresses of other networks are only valid with `any_net_type`
+        assert_eq!(validate(&address, Network::Mainnet, false), invalid);
+        assert_eq!(
+            validate(&address, Network::Mainnet, true),
+            valid(false, false)
+        );
+        assert_eq!(
+            validate_address("monero.example.com", Network::Mainnet, true),
+            invalid
+        );
+
+        assert!(check_address_network(&address, Network::Stagenet).is_ok());
+        assert_eq!(
+            check_address_network(&address, Network::Mainnet)
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "{} is a Stagenet address, expected a Mainnet address",
+                address
+            )
+        );
+        assert_eq!(network_from_nettype("fakechain"), Some(Network::Mainnet));
+        assert_eq!(network_from_nettype(""), None);
+    }
+}
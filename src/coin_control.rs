@@ -0,0 +1,180 @@
+// Copyright 2019-2022 Artem Vorotnikov and Monero Rust Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    IncomingTransfer, KeyImage, TransferData, TransferOptions, TransferPriority, TransferType,
+    WalletClient,
+};
+use monero::{Address, Amount};
+use std::cmp::Reverse;
+use tracing::*;
+
+/// Select spendable outputs, largest first, until they cover `amount`, and return their key
+/// images. Spent, locked and frozen outputs are skipped, as well as outputs without a key image.
+///
+/// `amount` should include a margin for the fee. Returns `None` if the outputs do not cover it.
+pub fn select_outputs(outputs: &[IncomingTransfer], amount: Amount) -> Option<Vec<KeyImage>> {
+    let mut spendable = vec![];
+    for output in outputs {
+        if output.spent || output.frozen == Some(true) || output.unlocked == Some(false) {
+            continue;
+        }
+        if let Some(key_image) = &output.key_image {
+            spendable.push((output.amount, key_image.0));
+        }
+    }
+    spendable.sort_by_key(|(amount, _)| Reverse(*amount));
+
+    let mut selected = vec![];
+    let mut total = Amount::ZERO;
+    for (output_amount, key_image) in spendable {
+        if total >= amount {
+            break;
+        }
+        total += output_amount;
+        selected.push(key_image);
+    }
+
+    if total >= amount {
+        Some(selected)
+    } else {
+        None
+    }
+}
+
+impl WalletClient {
+    /// Same as [`Self::transfer`], but only spend the outputs with the given key images, e.g.
+    /// picked with [`select_outputs`].
+    ///
+    /// The other available outputs of `options.account_index`, as returned by
+    /// `incoming_transfers`, are frozen around the transfer, and thawed afterwards even if the
+    /// transfer fails. Outputs that were already frozen are left untouched. When the transfer
+    /// succeeded but thawing failed, the error is logged and the transfer is still returned, so
+    /// that it is not retried.
+    ///
+    /// If the returned future is dropped between freezing and thawing, the other outputs stay
+    /// frozen; thaw them with [`Self::thaw`], e.g. those reported as frozen by
+    /// `incoming_transfers`. Concurrent calls on the same account race each other's freezes and
+    /// thaws, so callers must not run them in parallel.
+    pub async fn transfer_from_outputs(
+        &self,
+        key_images: &[KeyImage],
+        destinations: Vec<(Address, monero::Amount)>,
+        priority: TransferPriority,
+        options: TransferOptions,
+    ) -> anyhow::Result<TransferData> {
+        let outputs = self
+            .incoming_transfers(TransferType::Available, options.account_index, None)
+            .await?
+            .transfers
+            .unwrap_or_default();
+
+        let mut available = vec![];
+        let mut to_freeze = vec![];
+        for output in &outputs {
+            let key_image = match &output.key_image {
+                Some(key_image) => key_image.0,
+                None => continue,
+            };
+            if key_images.contains(&key_image) {
+                available.push(key_image);
+                continue;
+            }
+
+            let frozen = match output.frozen {
+                Some(frozen) => frozen,
+                None => self.frozen(key_image).await?,
+            };
+            if !frozen {
+                to_freeze.push(key_image);
+            }
+        }
+        if let Some(missing) = key_images.iter().find(|k| !available.contains(k)) {
+            anyhow::bail!("Output with key image {:x} is not available", missing);
+        }
+
+        let mut frozen = vec![];
+        let res = async {
+            for key_image in to_freeze {
+                self.freeze(key_image).await?;
+                frozen.push(key_image);
+            }
+            self.transfer(destinations, priority, options).await
+        }
+        .await;
+
+        for key_image in frozen {
+            if let Err(e) = self.thaw(key_image).await {
+                error!(
+                    "Failed to thaw output with key image {:x}: {}",
+                    key_image, e
+                );
+            }
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashString;
+    use monero::{cryptonote::subaddress, Hash};
+
+    fn output(amount: u64, key_image: u8) -> IncomingTransfer {
+        IncomingTransfer {
+            amount: Amount::from_pico(amount),
+            global_index: 0,
+            key_image: Some(HashString(KeyImage::repeat_byte(key_image))),
+            spent: false,
+            frozen: Some(false),
+            unlocked: Some(true),
+            subaddr_index: subaddress::Index { major: 0, minor: 0 },
+            tx_hash: HashString(Hash::zero()),
+            tx_size: None,
+        }
+    }
+
+    #[test]
+    fn select_outputs_largest_first() {
+        let mut outputs = vec![output(10, 1), output(30, 2), output(20, 3)];
+        assert_eq!(
+            select_outputs(&outputs, Amount::from_pico(40)),
+            Some(vec![KeyImage::repeat_byte(2), KeyImage::repeat_byte(3)])
+        );
+        assert_eq!(
+            select_outputs(&outputs, Amount::from_pico(30)),
+            Some(vec![KeyImage::repeat_byte(2)])
+        );
+        assert_eq!(select_outputs(&outputs, Amount::from_pico(61)), None);
+
+        // unspendable outputs
+        outputs[1].frozen = Some(true);
+        outputs[2].unlocked = Some(false);
+        outputs.push(IncomingTransfer {
+            spent: true,
+            ..output(50, 4)
+        });
+        outputs.push(IncomingTransfer {
+            key_image: None,
+            ..output(50, 5)
+        });
+        assert_eq!(
+            select_outputs(&outputs, Amount::from_pico(10)),
+            Some(vec![KeyImage::repeat_byte(1)])
+        );
+        assert_eq!(select_outputs(&outputs, Amount::from_pico(11)), None);
+    }
+}
@@ -0,0 +1,351 @@
+// Copyright 2019-2022 Artem Vorotnikov and Monero Rust Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{DaemonRpcClient, GetTransfersCategory, TransferHeight, WalletClient};
+use futures::future::{BoxFuture, FutureExt};
+use monero::cryptonote::hash::Hash as CryptoNoteHash;
+use std::{fmt, time::Duration};
+
+/// Status of a transaction, as returned by [`TxStatusSource::tx_status`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TxStatus {
+    /// The transaction is unknown.
+    NotFound,
+    /// The transaction is in the pool.
+    InPool,
+    /// The transaction was mined at `height`.
+    Mined { height: u64, confirmations: u64 },
+    /// The wallet marked the transaction as failed.
+    Failed,
+}
+
+/// Source of [`TxStatus`] for [`wait_for_confirmations`], implemented for [`WalletClient`] and
+/// [`DaemonRpcClient`].
+pub trait TxStatusSource {
+    fn tx_status(&self, txid: CryptoNoteHash) -> BoxFuture<'_, anyhow::Result<TxStatus>>;
+}
+
+impl TxStatusSource for WalletClient {
+    /// Use wallet `get_transfer_by_txid`, on the default account.
+    fn tx_status(&self, txid: CryptoNoteHash) -> BoxFuture<'_, anyhow::Result<TxStatus>> {
+        async move {
+            let transfer = match self.get_transfer(txid, None).await? {
+                Some(transfer) => transfer,
+                None => return Ok(TxStatus::NotFound),
+            };
+
+            Ok(match (transfer.transfer_type, transfer.height) {
+                (GetTransfersCategory::Failed, _) => TxStatus::Failed,
+                (_, TransferHeight::InPool) => TxStatus::InPool,
+                (_, TransferHeight::Confirmed(height)) => TxStatus::Mined {
+                    height: height.get(),
+                    confirmations: transfer.confirmations.unwrap_or_default(),
+                },
+            })
+        }
+        .boxed()
+    }
+}
+
+impl TxStatusSource for DaemonRpcClient {
+    /// Use daemon RPC `get_transactions`.
+    fn tx_status(&self, txid: CryptoNoteHash) -> BoxFuture<'_, anyhow::Result<TxStatus>> {
+        async move {
+            let rsp = self.get_transactions(vec![txid], None, Some(true)).await?;
+            let tx = match rsp.txs.and_then(|txs| txs.into_iter().next()) {
+                Some(tx) => tx,
+                None => return Ok(TxStatus::NotFound),
+            };
+
+            if tx.in_pool {
+                return Ok(TxStatus::InPool);
+            }
+
+            match (tx.block_height, tx.confirmations) {
+                (Some(height), Some(confirmations)) => Ok(TxStatus::Mined {
+                    height,
+                    confirmations,
+                }),
+                _ => anyhow::bail!("Daemon did not return the confirmations of {}", txid),
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Explicit failures of [`wait_for_confirmations`], which can be recovered from the returned
+/// [`anyhow::Error`] with `downcast_ref`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfirmationError {
+    /// The transaction did not reach the confirmations in time; holds its last status.
+    Timeout(TxStatus),
+    /// The transaction left the pool without being mined.
+    LeftPool,
+    /// The transaction disappeared after being mined at `height`, i.e. after a reorg.
+    Disappeared { height: u64 },
+    /// The wallet marked the transaction as failed.
+    Failed,
+}
+
+impl fmt::Display for ConfirmationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfirmationError::Timeout(status) => {
+                write!(
+                    f,
+                    "Timed out waiting for confirmations, last status: {:?}",
+                    status
+                )
+            }
+            ConfirmationError::LeftPool => {
+                write!(f, "Transaction left the pool without being mined")
+            }
+            ConfirmationError::Disappeared { height } => write!(
+                f,
+                "Transaction disappeared after being mined at height {}",
+                height
+            ),
+            ConfirmationError::Failed => write!(f, "Transaction failed"),
+        }
+    }
+}
+
+impl std::error::Error for ConfirmationError {}
+
+/// What [`wait_for_confirmations`] has seen of a transaction so far.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ConfirmationState {
+    seen_in_pool: bool,
+    mined_at: Option<u64>,
+}
+
+impl ConfirmationState {
+    /// Return the height of the transaction once it has `confirmations`.
+    pub(crate) fn update(
+        &mut self,
+        status: TxStatus,
+        confirmations: u64,
+    ) -> Result<Option<u64>, ConfirmationError> {
+        match status {
+            TxStatus::NotFound => {
+                if let Some(height) = self.mined_at {
+                    return Err(ConfirmationError::Disappeared { height });
+                }
+                if self.seen_in_pool {
+                    return Err(ConfirmationError::LeftPool);
+                }
+            }
+            // back in the pool after a reorg, it can still be mined again
+            TxStatus::InPool => {
+                self.seen_in_pool = true;
+                self.mined_at = None;
+            }
+            TxStatus::Mined {
+                height,
+                confirmations: current,
+            } => {
+                self.mined_at = Some(height);
+                if current >= confirmations {
+                    return Ok(Some(height));
+                }
+            }
+            TxStatus::Failed => return Err(ConfirmationError::Failed),
+        }
+
+        Ok(None)
+    }
+}
+
+/// Wait until `txid` has at least `confirmations`, polling `source` every `poll_interval`, and
+/// return the height of the block the transaction was mined in.
+///
+/// `on_progress` is called with every status that differs from the previous one. An unknown
+/// transaction is waited for, since it may not have reached `source` yet, but a transaction that
+/// leaves the pool without being mined, or disappears after being mined, fails with a
+/// [`ConfirmationError`], as well as reaching `timeout`. RPC errors are returned as they happen.
+///
+/// Dropping the returned future cancels the wait, e.g. with `futures::future::abortable`.
+pub async fn wait_for_confirmations<S, F>(
+    source: &S,
+    txid: CryptoNoteHash,
+    confirmations: u64,
+    poll_interval: Duration,
+    timeout: Option<Duration>,
+    mut on_progress: F,
+) -> anyhow::Result<u64>
+where
+    S: TxStatusSource + ?Sized,
+    F: FnMut(TxStatus),
+{
+    let mut state = ConfirmationState::default();
+    let mut last_status = None;
+
+    let wait = async {
+        loop {
+            let status = source.tx_status(txid).await?;
+            if last_status != Some(status) {
+                on_progress(status);
+                last_status = Some(status);
+            }
+
+            if let Some(height) = state.update(status, confirmations)? {
+                return Ok(height);
+            }
+
+            tokio::time::sleep(poll_interval).await;
+        }
+    };
+
+    match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, wait).await {
+            Ok(res) => res,
+            Err(_) => {
+                Err(ConfirmationError::Timeout(last_status.unwrap_or(TxStatus::NotFound)).into())
+            }
+        },
+        None => wait.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::VecDeque, sync::Mutex};
+
+    struct ScriptedSource(Mutex<VecDeque<TxStatus>>);
+
+    impl TxStatusSource for ScriptedSource {
+        fn tx_status(&self, _: CryptoNoteHash) -> BoxFuture<'_, anyhow::Result<TxStatus>> {
+            let mut statuses = self.0.lock().unwrap();
+            // the last status is repeated forever
+            let status = if statuses.len() > 1 {
+                statuses.pop_front().unwrap()
+            } else {
+                statuses[0]
+            };
+            futures::future::ready(Ok(status)).boxed()
+        }
+    }
+
+    fn mined(height: u64, confirmations: u64) -> TxStatus {
+        TxStatus::Mined {
+            height,
+            confirmations,
+        }
+    }
+
+    #[test]
+    fn confirmation_state_update() {
+        let mut state = ConfirmationState::default();
+        assert_eq!(state.update(TxStatus::NotFound, 2), Ok(None));
+        assert_eq!(state.update(TxStatus::InPool, 2), Ok(None));
+        assert_eq!(state.update(mined(10, 1), 2), Ok(None));
+        // reorg back to the pool, then mined again
+        assert_eq!(state.update(TxStatus::InPool, 2), Ok(None));
+        assert_eq!(state.update(mined(11, 2), 2), Ok(Some(11)));
+
+        let mut state = ConfirmationState::default();
+        state.update(TxStatus::InPool, 2).unwrap();
+        assert_eq!(
+            state.update(TxStatus::NotFound, 2),
+            Err(ConfirmationError::LeftPool)
+        );
+
+        let mut state = ConfirmationState::default();
+        state.update(mined(10, 1), 2).unwrap();
+        assert_eq!(
+            state.update(TxStatus::NotFound, 2),
+            Err(ConfirmationError::Disappeared { height: 10 })
+        );
+
+        let mut state = ConfirmationState::default();
+        assert_eq!(
+            state.update(TxStatus::Failed, 2),
+            Err(ConfirmationError::Failed)
+        );
+    }
+
+    #[tokio::test]
+    async fn wait_for_confirmations_reports_progress() {
+        let source = ScriptedSource(Mutex::new(
+            vec![
+                TxStatus::NotFound,
+                TxStatus::InPool,
+                TxStatus::InPool,
+                mined(10, 1),
+                mined(10, 2),
+                mined(10, 3),
+            ]
+            .into(),
+        ));
+
+        let mut progress = vec![];
+        let height = wait_for_confirmations(
+            &source,
+            CryptoNoteHash::zero(),
+            2,
+            Duration::from_millis(1),
+            None,
+            |status| progress.push(status),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(height, 10);
+        assert_eq!(
+            progress,
+            vec![
+                TxStatus::NotFound,
+                TxStatus::InPool,
+                mined(10, 1),
+                mined(10, 2)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn wait_for_confirmations_failures() {
+        let source = ScriptedSource(Mutex::new(vec![TxStatus::InPool].into()));
+        let err = wait_for_confirmations(
+            &source,
+            CryptoNoteHash::zero(),
+            1,
+            Duration::from_millis(1),
+            Some(Duration::from_millis(20)),
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ConfirmationError>(),
+            Some(&ConfirmationError::Timeout(TxStatus::InPool))
+        );
+
+        let source = ScriptedSource(Mutex::new(vec![mined(10, 1), TxStatus::NotFound].into()));
+        let err = wait_for_confirmations(
+            &source,
+            CryptoNoteHash::zero(),
+            2,
+            Duration::from_millis(1),
+            None,
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Transaction disappeared after being mined at height 10"
+        );
+    }
+}
@@ -30,6 +30,9 @@
 //! let daemon = client.daemon();
 //! let regtest_daemon = daemon.regtest();
 //! ```
+//!
+//! Call [`RpcClient::with_network`] first to make sure the daemon or the wallet is on the expected
+//! Monero network, and that no address of another network is sent to it or returned by it.
 
 #![forbid(unsafe_code)]
 
@@ -37,16 +40,35 @@ pub use monero;
 
 #[macro_use]
 mod util;
+mod addresses;
+mod coin_control;
+mod confirmations;
 mod models;
+mod multisig;
+mod network;
+mod payments;
+mod payouts;
+mod reserves;
+mod signatures;
+mod streams;
+#[cfg(feature = "zmq")]
+mod zmq;
+
+#[cfg(feature = "zmq")]
+pub use self::zmq::*;
+pub use self::{
+    addresses::*, coin_control::*, confirmations::*, models::*, multisig::*, payments::*,
+    payouts::*, reserves::*, signatures::*, streams::*, util::*,
+};
 
-pub use self::{models::*, util::*};
-
+use futures::{stream, StreamExt, TryStreamExt};
 use jsonrpc_core::types::{Id, *};
 use monero::{
     cryptonote::{hash::Hash as CryptoNoteHash, subaddress},
     util::{address::PaymentId, amount},
-    Address, Amount,
+    Address, AddressType, Amount, Network,
 };
+use network::{NetworkBinding, Server};
 use serde::{de::IgnoredAny, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{json, Value};
 use std::{
@@ -160,14 +182,86 @@ impl RemoteCaller {
 }
 
 #[derive(Clone, Debug)]
-struct CallerWrapper(Arc<RemoteCaller>);
+struct CallerWrapper {
+    caller: Arc<RemoteCaller>,
+    network: Option<NetworkBinding>,
+}
 
 impl CallerWrapper {
+    /// Check once that the server is on the bound network, if any.
+    async fn check_network(&self) -> anyhow::Result<()> {
+        let binding = match &self.network {
+            Some(binding) if !binding.is_checked() => binding,
+            _ => return Ok(()),
+        };
+
+        let network = match binding.server() {
+            Server::Daemon => {
+                #[derive(Deserialize)]
+                struct Rsp {
+                    nettype: String,
+                }
+
+                let rsp = self
+                    .request_unchecked::<MoneroResult<Rsp>>("get_info", RpcParams::None)
+                    .await?
+                    .into_inner();
+                network_from_nettype(&rsp.nettype)
+            }
+            Server::Wallet => {
+                #[derive(Deserialize)]
+                struct Rsp {
+                    address: Address,
+                }
+
+                let params = once(("account_index", 0.into()));
+                let rsp = self
+                    .request_unchecked::<Rsp>("get_address", RpcParams::map(params))
+                    .await?;
+                Some(rsp.address.network)
+            }
+        };
+
+        binding.check_server(network)
+    }
+
+    /// Check the server again on next use, e.g. after opening another wallet.
+    fn reset_network_check(&self) {
+        if let Some(binding) = &self.network {
+            binding.reset();
+        }
+    }
+
+    /// Fail unless all of `addresses` are addresses of the bound network, if any.
+    fn check_addresses<'a>(
+        &self,
+        addresses: impl IntoIterator<Item = &'a Address>,
+    ) -> anyhow::Result<()> {
+        match &self.network {
+            Some(binding) => binding.check_addresses(addresses),
+            None => Ok(()),
+        }
+    }
+
     async fn request<T>(&self, method: &'static str, params: RpcParams) -> anyhow::Result<T>
     where
         T: for<'de> Deserialize<'de> + Send + 'static,
     {
-        let c = self.0.json_rpc_call(method, params);
+        self.check_network().await?;
+        self.request_unchecked(method, params).await
+    }
+
+    /// Same as [`Self::request`], without checking the network of the server, for the methods
+    /// that do not need it, such as opening a wallet.
+    async fn request_unchecked<T>(
+        &self,
+        method: &'static str,
+        params: RpcParams,
+    ) -> anyhow::Result<T>
+    where
+        T: for<'de> Deserialize<'de> + Send + 'static,
+    {
+        let c = self.caller.json_rpc_call(method, params);
         Ok(serde_json::from_value(c.await??)?)
     }
 
@@ -179,7 +273,8 @@ impl CallerWrapper {
     where
         T: for<'de> Deserialize<'de> + Send + 'static + Debug,
     {
-        let c = self.0.daemon_rpc_call(method, params).await?;
+        self.check_network().await?;
+        let c = self.caller.daemon_rpc_call(method, params).await?;
         Ok(serde_json::from_value(c)?)
     }
 }
@@ -188,39 +283,70 @@ impl CallerWrapper {
 /// transform it into a specialized client.
 #[derive(Clone, Debug)]
 pub struct RpcClient {
-    inner: CallerWrapper,
+    caller: Arc<RemoteCaller>,
+    network: Option<Network>,
 }
 
 impl RpcClient {
     /// Create a new generic RPC client that can be transformed into specialized client.
     pub fn new(addr: String) -> Self {
         Self {
-            inner: CallerWrapper(Arc::new(RemoteCaller {
+            caller: Arc::new(RemoteCaller {
                 http_client: reqwest::ClientBuilder::new().build().unwrap(),
                 addr,
-            })),
+            }),
+            network: None,
+        }
+    }
+
+    /// Bind the specialized clients to `network`. On first use, they check that the daemon, or
+    /// the opened wallet, is on `network`, and fail otherwise. They also reject the addresses of
+    /// other networks, given as arguments or returned by the server.
+    ///
+    /// ```rust
+    /// use monero_rpc::{monero::Network, RpcClient};
+    ///
+    /// let client = RpcClient::new("http://127.0.0.1:38081".to_string());
+    /// let daemon = client.with_network(Network::Stagenet).daemon();
+    /// ```
+    pub fn with_network(self, network: Network) -> Self {
+        Self {
+            network: Some(network),
+            ..self
+        }
+    }
+
+    fn caller(self, server: Server) -> CallerWrapper {
+        CallerWrapper {
+            caller: self.caller,
+            network: self
+                .network
+                .map(|network| NetworkBinding::new(network, server)),
         }
     }
 
     /// Transform the client into the specialized `DaemonJsonRpcClient` that interacts with JSON RPC
     /// methods on daemon.
     pub fn daemon(self) -> DaemonJsonRpcClient {
-        let Self { inner } = self;
-        DaemonJsonRpcClient { inner }
+        DaemonJsonRpcClient {
+            inner: self.caller(Server::Daemon),
+        }
     }
 
     /// Transform the client into the specialized `DaemonRpcClient` that interacts with methods on
     /// daemon called with their own extensions.
     pub fn daemon_rpc(self) -> DaemonRpcClient {
-        let Self { inner } = self;
-        DaemonRpcClient { inner }
+        DaemonRpcClient {
+            inner: self.caller(Server::Daemon),
+        }
     }
 
     /// Transform the client into the specialized `WalletClient` that interacts with a Monero
     /// wallet RPC daemon.
     pub fn wallet(self) -> WalletClient {
-        let Self { inner } = self;
-        WalletClient { inner }
+        WalletClient {
+            inner: self.caller(Server::Wallet),
+        }
     }
 }
 
@@ -307,6 +433,7 @@ impl DaemonJsonRpcClient {
         wallet_address: Address,
         reserve_size: u64,
     ) -> anyhow::Result<BlockTemplate> {
+        self.inner.check_addresses(once(&wallet_address))?;
         Ok(self
             .inner
             .request::<MoneroResult<BlockTemplate>>(
@@ -394,6 +521,34 @@ impl DaemonJsonRpcClient {
         Ok((headers.into_iter().map(From::from).collect(), untrusted))
     }
 
+    /// Same as [`Self::get_block_headers_range`], but split `range` into ranges of at most
+    /// `chunk_size` blocks, to stay below the limit of restricted daemons (see
+    /// [`RESTRICTED_BLOCK_HEADERS_RANGE_LIMIT`]). At most `concurrency` ranges are requested at
+    /// the same time; headers are returned in order, and are untrusted if any range is.
+    pub async fn get_block_headers_range_chunked(
+        &self,
+        range: RangeInclusive<u64>,
+        chunk_size: u64,
+        concurrency: usize,
+    ) -> anyhow::Result<(Vec<BlockHeaderResponse>, bool)> {
+        anyhow::ensure!(chunk_size > 0, "chunk_size must be greater than 0");
+        anyhow::ensure!(concurrency > 0, "concurrency must be greater than 0");
+
+        let responses = stream::iter(split_range(range, chunk_size))
+            .map(|range| self.get_block_headers_range(range))
+            .buffered(concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        Ok(responses.into_iter().fold(
+            (vec![], false),
+            |(mut headers, untrusted), (chunk, chunk_untrusted)| {
+                headers.extend(chunk);
+                (headers, untrusted || chunk_untrusted)
+            },
+        ))
+    }
+
     /// Enable additional functions for daemons in regtest mode.
     pub fn regtest(self) -> RegtestDaemonJsonRpcClient {
         RegtestDaemonJsonRpcClient(self)
@@ -441,6 +596,116 @@ impl DaemonRpcClient {
             .daemon_rpc_request::<TransactionsResponse>("get_transactions", RpcParams::map(params))
             .await
     }
+
+    /// Get the hashes of the transactions in the pool.
+    pub async fn get_transaction_pool_hashes(
+        &self,
+    ) -> anyhow::Result<TransactionPoolHashesResponse> {
+        self.inner
+            .daemon_rpc_request::<TransactionPoolHashesResponse>(
+                "get_transaction_pool_hashes",
+                RpcParams::map(empty()),
+            )
+            .await
+    }
+
+    /// Get statistics about the transactions in the pool.
+    pub async fn get_transaction_pool_stats(&self) -> anyhow::Result<TxPoolStats> {
+        #[derive(Deserialize, Debug)]
+        struct Rsp {
+            pool_stats: TxPoolStats,
+        }
+
+        Ok(self
+            .inner
+            .daemon_rpc_request::<Rsp>("get_transaction_pool_stats", RpcParams::map(empty()))
+            .await?
+            .pool_stats)
+    }
+
+    /// Same as [`Self::get_transactions`], but split `txs_hashes` into chunks of at most
+    /// `chunk_size` hashes, to stay below the limit of restricted daemons (see
+    /// [`RESTRICTED_GET_TRANSACTIONS_LIMIT`]). At most `concurrency` chunks are requested at the
+    /// same time, and the responses are merged in the order of `txs_hashes`.
+    pub async fn get_transactions_chunked(
+        &self,
+        txs_hashes: Vec<CryptoNoteHash>,
+        decode_as_json: Option<bool>,
+        prune: Option<bool>,
+        chunk_size: usize,
+        concurrency: usize,
+    ) -> anyhow::Result<TransactionsResponse> {
+        anyhow::ensure!(chunk_size > 0, "chunk_size must be greater than 0");
+        anyhow::ensure!(concurrency > 0, "concurrency must be greater than 0");
+
+        if txs_hashes.len() <= chunk_size {
+            return self
+                .get_transactions(txs_hashes, decode_as_json, prune)
+                .await;
+        }
+
+        let responses = stream::iter(txs_hashes.chunks(chunk_size).map(<[_]>::to_vec))
+            .map(|chunk| self.get_transactions(chunk, decode_as_json, prune))
+            .buffered(concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        Ok(merge_transactions_responses(responses))
+    }
+}
+
+/// Maximum number of transactions a restricted daemon returns in one `get_transactions` call.
+pub const RESTRICTED_GET_TRANSACTIONS_LIMIT: usize = 100;
+
+/// Maximum number of headers a restricted daemon returns in one `get_block_headers_range` call.
+pub const RESTRICTED_BLOCK_HEADERS_RANGE_LIMIT: u64 = 1000;
+
+fn split_range(range: RangeInclusive<u64>, chunk_size: u64) -> Vec<RangeInclusive<u64>> {
+    let (mut start, end) = range.into_inner();
+
+    // let the daemon report invalid ranges
+    if start > end {
+        return vec![start..=end];
+    }
+
+    let mut ranges = vec![];
+    loop {
+        let chunk_end = start.saturating_add(chunk_size - 1).min(end);
+        ranges.push(start..=chunk_end);
+        if chunk_end == end {
+            return ranges;
+        }
+        start = chunk_end + 1;
+    }
+}
+
+fn merge_transactions_responses(responses: Vec<TransactionsResponse>) -> TransactionsResponse {
+    fn extend<T>(merged: &mut Option<Vec<T>>, chunk: Option<Vec<T>>) {
+        if let Some(chunk) = chunk {
+            merged.get_or_insert_with(Vec::new).extend(chunk);
+        }
+    }
+
+    let mut responses = responses.into_iter();
+    let mut merged = match responses.next() {
+        Some(first) => first,
+        None => unreachable!("at least one chunk is always requested"),
+    };
+
+    for rsp in responses {
+        merged.credits += rsp.credits;
+        merged.top_hash = rsp.top_hash;
+        if merged.status == "OK" {
+            merged.status = rsp.status;
+        }
+        extend(&mut merged.missed_tx, rsp.missed_tx);
+        extend(&mut merged.txs, rsp.txs);
+        extend(&mut merged.txs_as_hex, rsp.txs_as_hex);
+        extend(&mut merged.txs_as_json, rsp.txs_as_json);
+        merged.untrusted |= rsp.untrusted;
+    }
+
+    merged
 }
 
 impl RegtestDaemonJsonRpcClient {
@@ -450,6 +715,7 @@ impl RegtestDaemonJsonRpcClient {
         amount_of_blocks: u64,
         wallet_address: Address,
     ) -> anyhow::Result<GenerateBlocksResponse> {
+        self.inner.check_addresses(once(&wallet_address))?;
         let params = empty()
             .chain(once((
                 "amount_of_blocks",
@@ -520,6 +786,79 @@ impl<'de> Deserialize<'de> for TransferPriority {
     }
 }
 
+/// Parameters of wallet `transfer` and `transfer_split`, which only differ by the name of the
+/// `get_tx_key` parameter.
+fn transfer_params(
+    destinations: Vec<(Address, monero::Amount)>,
+    priority: TransferPriority,
+    options: TransferOptions,
+    tx_key_param: &'static str,
+) -> anyhow::Result<RpcParams> {
+    let params = empty()
+        .chain(once((
+            "destinations",
+            destinations
+                .into_iter()
+                .map(|(address, amount)| json!({"address": address, "amount": amount.as_pico()}))
+                .collect::<Vec<Value>>()
+                .into(),
+        )))
+        .chain(once(("priority", serde_json::to_value(priority)?)))
+        .chain(options.account_index.map(|v| ("account_index", v.into())))
+        .chain(options.subaddr_indices.map(|v| {
+            (
+                "subaddr_indices",
+                v.into_iter().map(From::from).collect::<Vec<Value>>().into(),
+            )
+        }))
+        .chain(options.mixin.map(|v| ("mixin", v.into())))
+        .chain(options.ring_size.map(|v| ("ring_size", v.into())))
+        .chain(options.unlock_time.map(|v| ("unlock_time", v.into())))
+        .chain(
+            options
+                .payment_id
+                .map(|v| ("payment_id", serde_json::to_value(HashString(v)).unwrap())),
+        )
+        .chain(options.do_not_relay.map(|v| ("do_not_relay", v.into())))
+        .chain(options.subtract_fee_from_outputs.map(|v| {
+            (
+                "subtract_fee_from_outputs",
+                v.into_iter().map(From::from).collect::<Vec<Value>>().into(),
+            )
+        }))
+        .chain(once((
+            tx_key_param,
+            options.get_tx_key.unwrap_or(true).into(),
+        )))
+        .chain(once((
+            "get_tx_hex",
+            options.get_tx_hex.unwrap_or(true).into(),
+        )))
+        .chain(once((
+            "get_tx_metadata",
+            options.get_tx_metadata.unwrap_or(true).into(),
+        )));
+
+    Ok(RpcParams::map(params))
+}
+
+/// Parameters of [`SweepOptions`]; `sweep_single` names the `get_tx_keys` parameter
+/// `get_tx_key`.
+fn sweep_options_params(
+    options: SweepOptions,
+    tx_key_param: &'static str,
+) -> impl Iterator<Item = (&'static str, Value)> + Send {
+    empty()
+        .chain(options.get_tx_keys.map(|v| (tx_key_param, v.into())))
+        .chain(options.do_not_relay.map(|v| ("do_not_relay", v.into())))
+        .chain(options.get_tx_hex.map(|v| ("get_tx_hex", v.into())))
+        .chain(
+            options
+                .get_tx_metadata
+                .map(|v| ("get_tx_metadata", v.into())),
+        )
+}
+
 /// Result of [`RpcClient::wallet`] to interact with a Monero wallet RPC daemon.
 ///
 /// ```rust
@@ -534,12 +873,23 @@ pub struct WalletClient {
 }
 
 impl WalletClient {
+    /// The network the client is bound to with [`RpcClient::with_network`], if any.
+    pub fn network(&self) -> Option<Network> {
+        self.inner.network.as_ref().map(NetworkBinding::network)
+    }
+
+    /// Fail if `address` is not an address of the bound network, if any.
+    fn check_address(&self, address: &Address) -> anyhow::Result<()> {
+        self.inner.check_addresses(once(address))
+    }
+
     /// Generate a new wallet from viewkey, address and optionally a spend key.  Requires the rpc
     /// wallet to run with the `--wallet-dir` argument.
     pub async fn generate_from_keys(
         &self,
         args: GenerateFromKeysArgs,
     ) -> anyhow::Result<WalletCreation> {
+        self.check_address(&args.address)?;
         let params = empty()
             .chain(args.restore_height.map(|v| ("restore_height", v.into())))
             .chain(once(("filename", args.filename.into())))
@@ -551,9 +901,13 @@ impl WalletClient {
                 args.autosave_current
                     .map(|v| ("autosave_current", v.into())),
             );
-        self.inner
-            .request("generate_from_keys", RpcParams::map(params))
-            .await
+        let rsp = self
+            .inner
+            .request_unchecked::<WalletCreation>("generate_from_keys", RpcParams::map(params))
+            .await?;
+        self.inner.reset_network_check();
+        self.check_address(&rsp.address)?;
+        Ok(rsp)
     }
 
     /// Create a new wallet. You need to have set the argument `--wallet-dir` when launching
@@ -569,8 +923,9 @@ impl WalletClient {
             .chain(password.map(|v| ("password", v.into())))
             .chain(once(("language", language.into())));
         self.inner
-            .request::<IgnoredAny>("create_wallet", RpcParams::map(params))
+            .request_unchecked::<IgnoredAny>("create_wallet", RpcParams::map(params))
             .await?;
+        self.inner.reset_network_check();
         Ok(())
     }
 
@@ -586,8 +941,9 @@ impl WalletClient {
             .chain(password.map(|v| ("password", v.into())));
 
         self.inner
-            .request::<IgnoredAny>("open_wallet", RpcParams::map(params))
+            .request_unchecked::<IgnoredAny>("open_wallet", RpcParams::map(params))
             .await?;
+        self.inner.reset_network_check();
         Ok(())
     }
 
@@ -595,8 +951,9 @@ impl WalletClient {
     pub async fn close_wallet(&self) -> anyhow::Result<()> {
         let params = empty();
         self.inner
-            .request::<IgnoredAny>("close_wallet", RpcParams::map(params))
+            .request_unchecked::<IgnoredAny>("close_wallet", RpcParams::map(params))
             .await?;
+        self.inner.reset_network_check();
         Ok(())
     }
 
@@ -615,9 +972,13 @@ impl WalletClient {
                 )
             }));
 
+        let rsp = self
+            .inner
+            .request::<BalanceData>("get_balance", RpcParams::map(params))
+            .await?;
         self.inner
-            .request("get_balance", RpcParams::map(params))
-            .await
+            .check_addresses(rsp.per_subaddress.iter().map(|v| &v.address))?;
+        Ok(rsp)
     }
 
     /// Return the wallet's addresses for an account. Optionally filter for specific set of
@@ -636,9 +997,13 @@ impl WalletClient {
                 )
             }));
 
+        let rsp = self
+            .inner
+            .request::<AddressData>("get_address", RpcParams::map(params))
+            .await?;
         self.inner
-            .request("get_address", RpcParams::map(params))
-            .await
+            .check_addresses(once(&rsp.address).chain(rsp.addresses.iter().map(|v| &v.address)))?;
+        Ok(rsp)
     }
 
     /// Get account and address indexes from a specific (sub)address.
@@ -648,6 +1013,7 @@ impl WalletClient {
             index: subaddress::Index,
         }
 
+        self.check_address(&address)?;
         let params = once(("address", address.to_string().into()));
 
         let rsp = self
@@ -681,6 +1047,7 @@ impl WalletClient {
             .inner
             .request::<Rsp>("create_address", RpcParams::map(params))
             .await?;
+        self.check_address(&rsp.address)?;
 
         Ok((rsp.address, rsp.address_index))
     }
@@ -713,131 +1080,514 @@ impl WalletClient {
     pub async fn get_accounts(&self, tag: Option<String>) -> anyhow::Result<GetAccountsData> {
         let params = empty().chain(tag.map(|v| ("tag", v.into())));
 
+        let rsp = self
+            .inner
+            .request::<GetAccountsData>("get_accounts", RpcParams::map(params))
+            .await?;
         self.inner
-            .request("get_accounts", RpcParams::map(params))
-            .await
+            .check_addresses(rsp.subaddress_accounts.iter().map(|v| &v.base_address))?;
+
+        Ok(rsp)
     }
 
-    /// Get a list of incoming payments using a given payment id.
-    pub async fn get_payments(&self, payment_id: PaymentId) -> anyhow::Result<Vec<Payment>> {
+    /// Create a new account, and return its primary address and its index.
+    pub async fn create_account(&self, label: Option<String>) -> anyhow::Result<(Address, u32)> {
         #[derive(Deserialize)]
         struct Rsp {
-            #[serde(default)]
-            payments: Vec<Payment>,
+            address: Address,
+            account_index: u32,
         }
 
-        let params = empty().chain(once((
-            "payment_id",
-            HashString(payment_id).to_string().into(),
-        )));
+        let params = empty().chain(label.map(|v| ("label", Value::String(v))));
+
+        let rsp = self
+            .inner
+            .request::<Rsp>("create_account", RpcParams::map(params))
+            .await?;
+        self.check_address(&rsp.address)?;
+
+        Ok((rsp.address, rsp.account_index))
+    }
+
+    /// Label an account.
+    pub async fn label_account(&self, account_index: u32, label: String) -> anyhow::Result<()> {
+        let params = empty()
+            .chain(once(("account_index", account_index.into())))
+            .chain(once(("label", label.into())));
 
         self.inner
-            .request::<Rsp>("get_payments", RpcParams::map(params))
-            .await
-            .map(|rsp| rsp.payments)
+            .request::<IgnoredAny>("label_account", RpcParams::map(params))
+            .await?;
+
+        Ok(())
     }
 
-    /// Get a list of incoming payments using a given payment id, or a list of payments ids, from a
-    /// given height. This method is the preferred method over [`Self::get_payments`] because it
-    /// has the same functionality but is more extendable. Either is fine for looking up
-    /// transactions by a single payment ID.
-    pub async fn get_bulk_payments(
-        &self,
-        payment_ids: Vec<PaymentId>,
-        // It seems that the `min_block_height` argument is really optional, but the docs on the Monero website do not mention it
-        min_block_height: u64,
-    ) -> anyhow::Result<Vec<Payment>> {
+    /// Get the account tags of the wallet, with their description and accounts.
+    pub async fn get_account_tags(&self) -> anyhow::Result<Vec<AccountTag>> {
         #[derive(Deserialize)]
         struct Rsp {
             #[serde(default)]
-            payments: Vec<Payment>,
+            account_tags: Vec<AccountTag>,
         }
 
+        Ok(self
+            .inner
+            .request::<Rsp>("get_account_tags", RpcParams::None)
+            .await?
+            .account_tags)
+    }
+
+    /// Tag accounts, replacing their previous tag. The tag is created if needed.
+    pub async fn tag_accounts(&self, tag: String, accounts: Vec<u32>) -> anyhow::Result<()> {
         let params = empty()
-            .chain(once((
-                "payment_ids",
-                payment_ids
-                    .into_iter()
-                    .map(|s| HashString(s).to_string())
-                    .collect::<Vec<_>>()
-                    .into(),
-            )))
-            .chain(once(("min_block_height", min_block_height.into())));
+            .chain(once(("tag", tag.into())))
+            .chain(once(("accounts", accounts.into())));
 
         self.inner
-            .request::<Rsp>("get_bulk_payments", RpcParams::map(params))
-            .await
-            .map(|rsp| rsp.payments)
+            .request::<IgnoredAny>("tag_accounts", RpcParams::map(params))
+            .await?;
+
+        Ok(())
     }
 
-    /// Return the spend or view private key.
-    pub async fn query_key(
+    /// Remove the tag of accounts.
+    pub async fn untag_accounts(&self, accounts: Vec<u32>) -> anyhow::Result<()> {
+        let params = once(("accounts", accounts.into()));
+
+        self.inner
+            .request::<IgnoredAny>("untag_accounts", RpcParams::map(params))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set the description of an account tag.
+    pub async fn set_account_tag_description(
         &self,
-        key_selector: PrivateKeyType,
-    ) -> anyhow::Result<monero::PrivateKey> {
+        tag: String,
+        description: String,
+    ) -> anyhow::Result<()> {
+        let params = empty()
+            .chain(once(("tag", tag.into())))
+            .chain(once(("description", description.into())));
+
+        self.inner
+            .request::<IgnoredAny>("set_account_tag_description", RpcParams::map(params))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get the entries of the address book with the given indices, or all of them if `entries` is
+    /// empty.
+    pub async fn get_address_book(
+        &self,
+        entries: Vec<u64>,
+    ) -> anyhow::Result<Vec<AddressBookEntry>> {
         #[derive(Deserialize)]
-        struct Rsp {
-            key: HashString<Vec<u8>>,
+        struct Entry {
+            index: u64,
+            address: Address,
+            description: String,
         }
 
-        let params = empty().chain({
-            match key_selector {
-                PrivateKeyType::View => empty().chain(once(("key_type", "view_key".into()))),
-                PrivateKeyType::Spend => empty().chain(once(("key_type", "spend_key".into()))),
+        impl From<Entry> for AddressBookEntry {
+            fn from(value: Entry) -> Self {
+                let payment_id = match value.address.addr_type {
+                    AddressType::Integrated(payment_id) => Some(payment_id),
+                    _ => None,
+                };
+                Self {
+                    index: value.index,
+                    address: value.address,
+                    description: value.description,
+                    payment_id,
+                }
             }
-        });
+        }
+
+        #[derive(Deserialize)]
+        struct Rsp {
+            #[serde(default)]
+            entries: Vec<Entry>,
+        }
+
+        let params = once(("entries", entries.into()));
+
         let rsp = self
             .inner
-            .request::<Rsp>("query_key", RpcParams::map(params))
+            .request::<Rsp>("get_address_book", RpcParams::map(params))
             .await?;
+        self.inner
+            .check_addresses(rsp.entries.iter().map(|v| &v.address))?;
 
-        Ok(monero::PrivateKey::from_slice(&rsp.key.0)?)
+        Ok(rsp.entries.into_iter().map(From::from).collect())
     }
 
-    /// Returns the wallet's current block height.
-    pub async fn get_height(&self) -> anyhow::Result<NonZeroU64> {
+    /// Add an entry to the address book, and return its index. Use an integrated address to
+    /// store a payment id with it.
+    pub async fn add_address_book(
+        &self,
+        address: Address,
+        description: Option<String>,
+    ) -> anyhow::Result<u64> {
         #[derive(Deserialize)]
         struct Rsp {
-            height: NonZeroU64,
+            index: u64,
         }
 
+        self.check_address(&address)?;
+        let params = empty()
+            .chain(once(("address", address.to_string().into())))
+            .chain(description.map(|v| ("description", v.into())));
+
         Ok(self
             .inner
-            .request::<Rsp>("get_height", RpcParams::None)
+            .request::<Rsp>("add_address_book", RpcParams::map(params))
             .await?
-            .height)
+            .index)
     }
 
-    /// Send all unlocked balance to an address.
-    pub async fn sweep_all(&self, args: SweepAllArgs) -> anyhow::Result<SweepAllData> {
+    /// Edit the address and/or the description of an address book entry; `None` leaves them
+    /// unchanged.
+    pub async fn edit_address_book(
+        &self,
+        index: u64,
+        address: Option<Address>,
+        description: Option<String>,
+    ) -> anyhow::Result<()> {
+        self.inner.check_addresses(&address)?;
         let params = empty()
-            .chain(once(("address", args.address.to_string().into())))
-            .chain(once(("account_index", args.account_index.into())))
-            .chain(args.subaddr_indices.map(|v| ("subaddr_indices", v.into())))
-            .chain(once(("priority", serde_json::to_value(args.priority)?)))
-            .chain(once(("mixin", args.mixin.into())))
-            .chain(once(("ring_size", args.ring_size.into())))
-            .chain(once(("unlock_time", args.unlock_time.into())))
-            .chain(args.get_tx_keys.map(|v| ("get_tx_keys", v.into())))
-            .chain(
-                args.below_amount
-                    .map(|v| ("below_amount", v.as_pico().into())),
-            )
-            .chain(args.do_not_relay.map(|v| ("do_not_relay", v.into())))
-            .chain(args.get_tx_hex.map(|v| ("get_tx_hex", v.into())))
-            .chain(args.get_tx_metadata.map(|v| ("get_tx_metadata", v.into())));
+            .chain(once(("index", index.into())))
+            .chain(once(("set_address", address.is_some().into())))
+            .chain(address.map(|v| ("address", v.to_string().into())))
+            .chain(once(("set_description", description.is_some().into())))
+            .chain(description.map(|v| ("description", v.into())));
+
         self.inner
-            .request("sweep_all", RpcParams::map(params))
-            .await
+            .request::<IgnoredAny>("edit_address_book", RpcParams::map(params))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete an entry of the address book. The indices of the next entries are shifted down.
+    pub async fn delete_address_book(&self, index: u64) -> anyhow::Result<()> {
+        let params = once(("index", index.into()));
+
+        self.inner
+            .request::<IgnoredAny>("delete_address_book", RpcParams::map(params))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Make an integrated address from `standard_address`, or from the primary address of the
+    /// wallet, and `payment_id`, or a random one. Return the integrated address and its payment
+    /// id. See [`integrated_address`] to make it without a wallet-rpc.
+    pub async fn make_integrated_address(
+        &self,
+        standard_address: Option<Address>,
+        payment_id: Option<PaymentId>,
+    ) -> anyhow::Result<(Address, PaymentId)> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            integrated_address: Address,
+            payment_id: HashString<PaymentId>,
+        }
+
+        self.inner.check_addresses(&standard_address)?;
+        let params = empty()
+            .chain(standard_address.map(|v| ("standard_address", v.to_string().into())))
+            .chain(payment_id.map(|v| ("payment_id", HashString(v).to_string().into())));
+
+        let rsp = self
+            .inner
+            .request::<Rsp>("make_integrated_address", RpcParams::map(params))
+            .await?;
+        self.check_address(&rsp.integrated_address)?;
+
+        Ok((rsp.integrated_address, rsp.payment_id.0))
+    }
+
+    /// Split an integrated address into its standard address and payment id. See
+    /// [`split_integrated_address`] to split it without a wallet-rpc.
+    pub async fn split_integrated_address(
+        &self,
+        integrated_address: Address,
+    ) -> anyhow::Result<IntegratedAddressParts> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            standard_address: Address,
+            payment_id: HashString<PaymentId>,
+            is_subaddress: bool,
+        }
+
+        self.check_address(&integrated_address)?;
+        let params = once(("integrated_address", integrated_address.to_string().into()));
+
+        let rsp = self
+            .inner
+            .request::<Rsp>("split_integrated_address", RpcParams::map(params))
+            .await?;
+        self.check_address(&rsp.standard_address)?;
+
+        Ok(IntegratedAddressParts {
+            standard_address: rsp.standard_address,
+            payment_id: rsp.payment_id.0,
+            is_subaddress: rsp.is_subaddress,
+        })
+    }
+
+    /// Validate an address, or an OpenAlias when `allow_openalias` is set. Unless `any_net_type` is
+    /// set, the addresses of other networks than the one of the wallet are not valid. See
+    /// [`validate_address`] to validate an address without a wallet-rpc.
+    pub async fn validate_address(
+        &self,
+        address: String,
+        any_net_type: Option<bool>,
+        allow_openalias: Option<bool>,
+    ) -> anyhow::Result<AddressValidation> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            valid: bool,
+            #[serde(default)]
+            integrated: bool,
+            #[serde(default)]
+            subaddress: bool,
+            #[serde(default)]
+            nettype: String,
+            #[serde(
+                default,
+                deserialize_with = "models::deserialize_empty_address_as_none"
+            )]
+            openalias_address: Option<Address>,
+        }
+
+        let params = empty()
+            .chain(once(("address", address.into())))
+            .chain(any_net_type.map(|v| ("any_net_type", v.into())))
+            .chain(allow_openalias.map(|v| ("allow_openalias", v.into())));
+
+        let rsp = self
+            .inner
+            .request::<Rsp>("validate_address", RpcParams::map(params))
+            .await?;
+        self.inner.check_addresses(&rsp.openalias_address)?;
+
+        Ok(AddressValidation {
+            valid: rsp.valid,
+            integrated: rsp.integrated,
+            subaddress: rsp.subaddress,
+            nettype: if rsp.valid {
+                network_from_nettype(&rsp.nettype)
+            } else {
+                None
+            },
+            openalias_address: rsp.openalias_address,
+        })
+    }
+
+    /// Get a list of incoming payments using a given payment id.
+    pub async fn get_payments(&self, payment_id: PaymentId) -> anyhow::Result<Vec<Payment>> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            #[serde(default)]
+            payments: Vec<Payment>,
+        }
+
+        let params = empty().chain(once((
+            "payment_id",
+            HashString(payment_id).to_string().into(),
+        )));
+
+        let rsp = self
+            .inner
+            .request::<Rsp>("get_payments", RpcParams::map(params))
+            .await?;
+        self.inner
+            .check_addresses(rsp.payments.iter().map(|v| &v.address))?;
+        Ok(rsp.payments)
+    }
+
+    /// Get a list of incoming payments using a given payment id, or a list of payments ids, from a
+    /// given height. This method is the preferred method over [`Self::get_payments`] because it
+    /// has the same functionality but is more extendable. Either is fine for looking up
+    /// transactions by a single payment ID.
+    pub async fn get_bulk_payments(
+        &self,
+        payment_ids: Vec<PaymentId>,
+        // It seems that the `min_block_height` argument is really optional, but the docs on the Monero website do not mention it
+        min_block_height: u64,
+    ) -> anyhow::Result<Vec<Payment>> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            #[serde(default)]
+            payments: Vec<Payment>,
+        }
+
+        let params = empty()
+            .chain(once((
+                "payment_ids",
+                payment_ids
+                    .into_iter()
+                    .map(|s| HashString(s).to_string())
+                    .collect::<Vec<_>>()
+                    .into(),
+            )))
+            .chain(once(("min_block_height", min_block_height.into())));
+
+        let rsp = self
+            .inner
+            .request::<Rsp>("get_bulk_payments", RpcParams::map(params))
+            .await?;
+        self.inner
+            .check_addresses(rsp.payments.iter().map(|v| &v.address))?;
+        Ok(rsp.payments)
+    }
+
+    /// Return the spend or view private key.
+    pub async fn query_key(
+        &self,
+        key_selector: PrivateKeyType,
+    ) -> anyhow::Result<monero::PrivateKey> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            key: HashString<Vec<u8>>,
+        }
+
+        let params = empty().chain({
+            match key_selector {
+                PrivateKeyType::View => empty().chain(once(("key_type", "view_key".into()))),
+                PrivateKeyType::Spend => empty().chain(once(("key_type", "spend_key".into()))),
+            }
+        });
+        let rsp = self
+            .inner
+            .request::<Rsp>("query_key", RpcParams::map(params))
+            .await?;
+
+        Ok(monero::PrivateKey::from_slice(&rsp.key.0)?)
+    }
+
+    /// Returns the wallet's current block height.
+    pub async fn get_height(&self) -> anyhow::Result<NonZeroU64> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            height: NonZeroU64,
+        }
+
+        Ok(self
+            .inner
+            .request::<Rsp>("get_height", RpcParams::None)
+            .await?
+            .height)
+    }
+
+    /// Send all unlocked balance to an address.
+    pub async fn sweep_all(&self, args: SweepAllArgs) -> anyhow::Result<SweepData> {
+        self.check_address(&args.address)?;
+        let params = empty()
+            .chain(once(("address", args.address.to_string().into())))
+            .chain(once(("account_index", args.account_index.into())))
+            .chain(args.subaddr_indices.map(|v| ("subaddr_indices", v.into())))
+            .chain(
+                args.subaddr_indices_all
+                    .map(|v| ("subaddr_indices_all", v.into())),
+            )
+            .chain(once(("priority", serde_json::to_value(args.priority)?)))
+            .chain(once(("mixin", args.mixin.into())))
+            .chain(once(("ring_size", args.ring_size.into())))
+            .chain(once(("unlock_time", args.unlock_time.into())))
+            .chain(args.outputs.map(|v| ("outputs", v.into())))
+            .chain(
+                args.below_amount
+                    .map(|v| ("below_amount", v.as_pico().into())),
+            )
+            .chain(sweep_options_params(args.options, "get_tx_keys"));
+        self.inner
+            .request("sweep_all", RpcParams::map(params))
+            .await
+    }
+
+    /// Send the output with the given key image to an address. The lists of the returned
+    /// [`SweepData`] have at most one element.
+    pub async fn sweep_single(&self, args: SweepSingleArgs) -> anyhow::Result<SweepData> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            tx_hash: HashString<CryptoNoteHash>,
+            tx_key: Option<HashString<TxKey>>,
+            #[serde(with = "amount::serde::as_pico")]
+            amount: Amount,
+            #[serde(with = "amount::serde::as_pico")]
+            fee: Amount,
+            weight: u64,
+            tx_blob: Option<HashString<TxBlob>>,
+            tx_metadata: Option<HashString<TxMetadata>>,
+            multisig_txset: HashString<MultisigTxSet>,
+            unsigned_txset: HashString<UnsignedTxSet>,
+        }
+
+        impl From<Rsp> for SweepData {
+            fn from(rsp: Rsp) -> Self {
+                // not requested values are missing or empty
+                fn non_empty<T: AsRef<[u8]>>(v: Option<HashString<T>>) -> Option<HashString<T>> {
+                    v.filter(|v| !v.0.as_ref().is_empty())
+                }
+
+                Self {
+                    tx_hash_list: vec![rsp.tx_hash],
+                    tx_key_list: non_empty(rsp.tx_key).into_iter().collect(),
+                    amount_list: vec![rsp.amount],
+                    fee_list: vec![rsp.fee],
+                    weight_list: vec![rsp.weight],
+                    tx_blob_list: non_empty(rsp.tx_blob).into_iter().collect(),
+                    tx_metadata_list: non_empty(rsp.tx_metadata).into_iter().collect(),
+                    multisig_txset: rsp.multisig_txset,
+                    unsigned_txset: rsp.unsigned_txset,
+                }
+            }
+        }
+
+        self.check_address(&args.address)?;
+        let params = empty()
+            .chain(once(("address", args.address.to_string().into())))
+            .chain(once(("key_image", serde_json::to_value(args.key_image)?)))
+            .chain(once(("priority", serde_json::to_value(args.priority)?)))
+            .chain(once(("ring_size", args.ring_size.into())))
+            .chain(once(("unlock_time", args.unlock_time.into())))
+            .chain(args.outputs.map(|v| ("outputs", v.into())))
+            .chain(sweep_options_params(args.options, "get_tx_key"));
+        Ok(self
+            .inner
+            .request::<Rsp>("sweep_single", RpcParams::map(params))
+            .await?
+            .into())
+    }
+
+    /// Send all the dust outputs back to the wallet, to make them spendable.
+    pub async fn sweep_dust(&self, options: SweepOptions) -> anyhow::Result<SweepData> {
+        let params = sweep_options_params(options, "get_tx_keys");
+        self.inner
+            .request("sweep_dust", RpcParams::map(params))
+            .await
+    }
+
+    /// Alias of [`Self::sweep_dust`], unmixable outputs being dust outputs.
+    pub async fn sweep_unmixable(&self, options: SweepOptions) -> anyhow::Result<SweepData> {
+        let params = sweep_options_params(options, "get_tx_keys");
+        self.inner
+            .request("sweep_unmixable", RpcParams::map(params))
+            .await
     }
 
     /// Relay a transaction previously created with `"do_not_relay":true`.
-    pub async fn relay_tx(&self, tx_metadata_hex: String) -> anyhow::Result<CryptoNoteHash> {
+    pub async fn relay_tx(&self, tx_metadata: TxMetadata) -> anyhow::Result<CryptoNoteHash> {
         #[derive(Deserialize)]
         struct Rsp {
             tx_hash: HashString<CryptoNoteHash>,
         }
-        let params = empty().chain(once(("hex", tx_metadata_hex.into())));
+        let params = empty().chain(once(("hex", HashString(tx_metadata).to_string().into())));
         Ok(self
             .inner
             .request::<Rsp>("relay_tx", RpcParams::map(params))
@@ -846,58 +1596,45 @@ impl WalletClient {
             .0)
     }
 
-    /// Send monero to a number of recipients.
+    /// Send monero to a number of recipients. The outputs follow the order of `destinations`, which
+    /// can hold the same address several times.
     pub async fn transfer(
         &self,
-        destinations: HashMap<Address, monero::Amount>,
+        destinations: Vec<(Address, monero::Amount)>,
         priority: TransferPriority,
         options: TransferOptions,
     ) -> anyhow::Result<TransferData> {
-        let params = empty()
-            .chain(once((
-                "destinations",
-                destinations
-                    .into_iter()
-                    .map(
-                        |(address, amount)| json!({"address": address, "amount": amount.as_pico()}),
-                    )
-                    .collect::<Vec<Value>>()
-                    .into(),
-            )))
-            .chain(once(("priority", serde_json::to_value(priority)?)))
-            .chain(options.account_index.map(|v| ("account_index", v.into())))
-            .chain(options.subaddr_indices.map(|v| {
-                (
-                    "subaddr_indices",
-                    v.into_iter().map(From::from).collect::<Vec<Value>>().into(),
-                )
-            }))
-            .chain(options.mixin.map(|v| ("mixin", v.into())))
-            .chain(options.ring_size.map(|v| ("ring_size", v.into())))
-            .chain(options.unlock_time.map(|v| ("unlock_time", v.into())))
-            .chain(
-                options
-                    .payment_id
-                    .map(|v| ("payment_id", serde_json::to_value(HashString(v)).unwrap())),
-            )
-            .chain(options.do_not_relay.map(|v| ("do_not_relay", v.into())))
-            .chain(once(("get_tx_key", true.into())))
-            .chain(once(("get_tx_hex", true.into())))
-            .chain(once(("get_tx_metadata", true.into())));
+        for (address, _) in &destinations {
+            self.check_address(address)?;
+        }
+        let params = transfer_params(destinations, priority, options, "get_tx_key")?;
+        self.inner.request("transfer", params).await
+    }
 
-        self.inner.request("transfer", RpcParams::map(params)).await
+    /// Same as [`Self::transfer`], but the payment can be split into several transactions.
+    pub async fn transfer_split(
+        &self,
+        destinations: Vec<(Address, monero::Amount)>,
+        priority: TransferPriority,
+        options: TransferOptions,
+    ) -> anyhow::Result<TransferSplitData> {
+        for (address, _) in &destinations {
+            self.check_address(address)?;
+        }
+        let params = transfer_params(destinations, priority, options, "get_tx_keys")?;
+        self.inner.request("transfer_split", params).await
     }
 
     /// Sign a transaction created on a read-only wallet (in cold-signing process).
     pub async fn sign_transfer(
         &self,
-        unsigned_txset: Vec<u8>,
+        unsigned_txset: UnsignedTxSet,
     ) -> anyhow::Result<SignedTransferOutput> {
         #[derive(Deserialize)]
         struct Rsp {
-            signed_txset: HashString<Vec<u8>>,
+            signed_txset: HashString<SignedTxSet>,
             tx_hash_list: Vec<HashString<CryptoNoteHash>>,
-            tx_raw_list: Vec<HashString<Vec<u8>>>,
+            tx_raw_list: Vec<HashString<TxBlob>>,
         }
 
         impl From<Rsp> for SignedTransferOutput {
@@ -926,7 +1663,7 @@ impl WalletClient {
     /// Submit a previously signed transaction on a read-only wallet (in cold-signing process).
     pub async fn submit_transfer(
         &self,
-        tx_data_hex: Vec<u8>,
+        signed_txset: SignedTxSet,
     ) -> anyhow::Result<Vec<CryptoNoteHash>> {
         #[derive(Deserialize)]
         struct Rsp {
@@ -935,7 +1672,7 @@ impl WalletClient {
 
         let params = empty().chain(once((
             "tx_data_hex",
-            HashString(tx_data_hex).to_string().into(),
+            HashString(signed_txset).to_string().into(),
         )));
 
         self.inner
@@ -1003,9 +1740,16 @@ impl WalletClient {
             .chain(min_height.map(|b| ("min_height", b.into())))
             .chain(max_height.map(|b| ("max_height", b.into())));
 
+        let rsp = self
+            .inner
+            .request::<HashMap<GetTransfersCategory, Vec<GotTransfer>>>(
+                "get_transfers",
+                RpcParams::map(params),
+            )
+            .await?;
         self.inner
-            .request("get_transfers", RpcParams::map(params))
-            .await
+            .check_addresses(rsp.values().flatten().map(|v| &v.address))?;
+        Ok(rsp)
     }
 
     /// Show information about a transfer to/from this address. **Calls `get_transfer_by_txid` in
@@ -1024,9 +1768,10 @@ impl WalletClient {
             .chain(Some(("txid", HashString(txid).to_string().into())))
             .chain(account_index.map(|v| ("account_index", v.into())));
 
+        self.inner.check_network().await?;
         let rsp = match self
             .inner
-            .0
+            .caller
             .json_rpc_call("get_transfer_by_txid", RpcParams::map(params))
             .await?
         {
@@ -1040,6 +1785,7 @@ impl WalletClient {
             }
         };
 
+        self.check_address(&rsp.transfer.address)?;
         Ok(Some(rsp.transfer))
     }
 
@@ -1050,7 +1796,7 @@ impl WalletClient {
     ) -> anyhow::Result<Vec<SignedKeyImage>> {
         #[derive(Deserialize)]
         struct R {
-            key_image: HashString<Vec<u8>>,
+            key_image: HashString<KeyImage>,
             signature: HashString<Vec<u8>>,
         }
 
@@ -1114,32 +1860,481 @@ impl WalletClient {
             .await
     }
 
+    /// Freeze the output with the given key image, so that it is not spent.
+    pub async fn freeze(&self, key_image: KeyImage) -> anyhow::Result<()> {
+        let params = once(("key_image", HashString(key_image).to_string().into()));
+
+        self.inner
+            .request::<IgnoredAny>("freeze", RpcParams::map(params))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Thaw an output frozen with [`Self::freeze`].
+    pub async fn thaw(&self, key_image: KeyImage) -> anyhow::Result<()> {
+        let params = once(("key_image", HashString(key_image).to_string().into()));
+
+        self.inner
+            .request::<IgnoredAny>("thaw", RpcParams::map(params))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Check whether the output with the given key image is frozen.
+    pub async fn frozen(&self, key_image: KeyImage) -> anyhow::Result<bool> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            frozen: bool,
+        }
+
+        let params = once(("key_image", HashString(key_image).to_string().into()));
+
+        Ok(self
+            .inner
+            .request::<Rsp>("frozen", RpcParams::map(params))
+            .await?
+            .frozen)
+    }
+
+    /// Get the secret key of a transaction sent by the wallet, followed by its additional keys.
+    pub async fn get_tx_key(&self, txid: CryptoNoteHash) -> anyhow::Result<TxKey> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            tx_key: HashString<TxKey>,
+        }
+
+        let params = once(("txid", HashString(txid).to_string().into()));
+
+        Ok(self
+            .inner
+            .request::<Rsp>("get_tx_key", RpcParams::map(params))
+            .await?
+            .tx_key
+            .0)
+    }
+
     /// Check a transaction in the blockchain with its secret key.
     pub async fn check_tx_key(
         &self,
         txid: CryptoNoteHash,
-        tx_key: Vec<u8>,
+        tx_key: TxKey,
+        address: Address,
+    ) -> anyhow::Result<TxKeyCheck> {
+        self.check_address(&address)?;
+        let params = empty()
+            .chain(once(("txid", HashString(txid).to_string().into())))
+            .chain(once(("tx_key", HashString(tx_key).to_string().into())))
+            .chain(once(("address", address.to_string().into())));
+
+        self.inner
+            .request("check_tx_key", RpcParams::map(params))
+            .await
+    }
+
+    /// Get a signature proving that the transaction paid `address`, or that it was received by
+    /// the wallet if `address` is one of its own, without revealing the secret key of the
+    /// transaction. `message` is signed along with it.
+    pub async fn get_tx_proof(
+        &self,
+        txid: CryptoNoteHash,
         address: Address,
-    ) -> anyhow::Result<(u64, bool, Amount)> {
+        message: Option<String>,
+    ) -> anyhow::Result<String> {
         #[derive(Deserialize)]
         struct Rsp {
-            confirmations: u64,
-            in_pool: bool,
-            #[serde(with = "amount::serde::as_pico")]
-            received: Amount,
+            signature: String,
         }
 
+        self.check_address(&address)?;
         let params = empty()
             .chain(once(("txid", HashString(txid).to_string().into())))
-            .chain(once(("tx_key", HashString(tx_key).to_string().into())))
-            .chain(once(("address", address.to_string().into())));
+            .chain(once(("address", address.to_string().into())))
+            .chain(message.map(|v| ("message", v.into())));
+
+        Ok(self
+            .inner
+            .request::<Rsp>("get_tx_proof", RpcParams::map(params))
+            .await?
+            .signature)
+    }
+
+    /// Check a signature returned by [`Self::get_tx_proof`].
+    pub async fn check_tx_proof(
+        &self,
+        txid: CryptoNoteHash,
+        address: Address,
+        message: Option<String>,
+        signature: String,
+    ) -> anyhow::Result<TxProofCheck> {
+        self.check_address(&address)?;
+        let params = empty()
+            .chain(once(("txid", HashString(txid).to_string().into())))
+            .chain(once(("address", address.to_string().into())))
+            .chain(message.map(|v| ("message", v.into())))
+            .chain(once(("signature", signature.into())));
+
+        self.inner
+            .request("check_tx_proof", RpcParams::map(params))
+            .await
+    }
+
+    /// Get a signature proving that the wallet sent the transaction, i.e. that it knows the
+    /// secret keys of the spent outputs. `message` is signed along with it.
+    pub async fn get_spend_proof(
+        &self,
+        txid: CryptoNoteHash,
+        message: Option<String>,
+    ) -> anyhow::Result<String> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            signature: String,
+        }
+
+        let params = empty()
+            .chain(once(("txid", HashString(txid).to_string().into())))
+            .chain(message.map(|v| ("message", v.into())));
+
+        Ok(self
+            .inner
+            .request::<Rsp>("get_spend_proof", RpcParams::map(params))
+            .await?
+            .signature)
+    }
+
+    /// Check a signature returned by [`Self::get_spend_proof`], and return whether it is valid.
+    pub async fn check_spend_proof(
+        &self,
+        txid: CryptoNoteHash,
+        message: Option<String>,
+        signature: String,
+    ) -> anyhow::Result<bool> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            good: bool,
+        }
+
+        let params = empty()
+            .chain(once(("txid", HashString(txid).to_string().into())))
+            .chain(message.map(|v| ("message", v.into())))
+            .chain(once(("signature", signature.into())));
+
+        Ok(self
+            .inner
+            .request::<Rsp>("check_spend_proof", RpcParams::map(params))
+            .await?
+            .good)
+    }
+
+    /// Sign `data` with the spend or view key of the subaddress `index`. `signature_type` needs a
+    /// v0.18 wallet-rpc; older ones ignore it and always sign with the spend key.
+    pub async fn sign(
+        &self,
+        data: String,
+        index: subaddress::Index,
+        signature_type: SignatureType,
+    ) -> anyhow::Result<String> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            signature: String,
+        }
+
+        let params = empty()
+            .chain(once(("data", data.into())))
+            .chain(once(("account_index", index.major.into())))
+            .chain(once(("address_index", index.minor.into())))
+            .chain(once((
+                "signature_type",
+                serde_json::to_value(signature_type)?,
+            )));
+
+        Ok(self
+            .inner
+            .request::<Rsp>("sign", RpcParams::map(params))
+            .await?
+            .signature)
+    }
+
+    /// Verify a signature of `data` made by `address` with [`Self::sign`]. See
+    /// [`verify_signature`] to verify it without a wallet-rpc.
+    pub async fn verify(
+        &self,
+        data: String,
+        address: Address,
+        signature: String,
+    ) -> anyhow::Result<SignatureCheck> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            good: bool,
+            #[serde(default)]
+            version: u32,
+            #[serde(default)]
+            old: bool,
+            // "invalid" when the signature is not valid
+            #[serde(default)]
+            signature_type: String,
+        }
+
+        impl From<Rsp> for SignatureCheck {
+            fn from(value: Rsp) -> Self {
+                Self {
+                    good: value.good,
+                    version: value.version,
+                    old: value.old,
+                    signature_type: match value.signature_type.as_str() {
+                        "spend" => Some(SignatureType::Spend),
+                        "view" => Some(SignatureType::View),
+                        _ => None,
+                    },
+                }
+            }
+        }
+
+        self.check_address(&address)?;
+        let params = empty()
+            .chain(once(("data", data.into())))
+            .chain(once(("address", address.to_string().into())))
+            .chain(once(("signature", signature.into())));
+
+        self.inner
+            .request::<Rsp>("verify", RpcParams::map(params))
+            .await
+            .map(From::from)
+    }
+
+    /// Get a signature proving the reserves of the wallet, i.e. that it owns unspent outputs of
+    /// at least the given amount. `message` is signed along with it.
+    pub async fn get_reserve_proof(
+        &self,
+        amount: ReserveProofAmount,
+        message: Option<String>,
+    ) -> anyhow::Result<String> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            signature: String,
+        }
+
+        let params: Vec<(&'static str, Value)> = match amount {
+            ReserveProofAmount::All => vec![("all", true.into())],
+            ReserveProofAmount::Account {
+                account_index,
+                amount,
+            } => vec![
+                ("all", false.into()),
+                ("account_index", account_index.into()),
+                ("amount", amount.as_pico().into()),
+            ],
+        };
+        let params = params
+            .into_iter()
+            .chain(message.map(|v| ("message", v.into())));
+
+        Ok(self
+            .inner
+            .request::<Rsp>("get_reserve_proof", RpcParams::map(params))
+            .await?
+            .signature)
+    }
+
+    /// Check a signature returned by [`Self::get_reserve_proof`] for the main address of the
+    /// wallet that made it.
+    pub async fn check_reserve_proof(
+        &self,
+        address: Address,
+        message: Option<String>,
+        signature: String,
+    ) -> anyhow::Result<ReserveProofCheck> {
+        self.check_address(&address)?;
+        let params = empty()
+            .chain(once(("address", address.to_string().into())))
+            .chain(message.map(|v| ("message", v.into())))
+            .chain(once(("signature", signature.into())));
+
+        self.inner
+            .request("check_reserve_proof", RpcParams::map(params))
+            .await
+    }
+
+    /// Check whether the wallet is multisig, and its M/N threshold.
+    pub async fn is_multisig(&self) -> anyhow::Result<MultisigStatus> {
+        self.inner.request("is_multisig", RpcParams::None).await
+    }
+
+    /// Prepare the wallet for multisig, and return the info to send to the other participants,
+    /// which they pass to [`Self::make_multisig`].
+    ///
+    /// Multisig is experimental in wallet-rpc and has to be enabled with
+    /// `enable_multisig_experimental` on servers that support it.
+    pub async fn prepare_multisig(
+        &self,
+        enable_multisig_experimental: bool,
+    ) -> anyhow::Result<String> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            multisig_info: String,
+        }
+
+        let params = once((
+            "enable_multisig_experimental",
+            enable_multisig_experimental.into(),
+        ));
+
+        Ok(self
+            .inner
+            .request::<Rsp>("prepare_multisig", RpcParams::map(params))
+            .await?
+            .multisig_info)
+    }
+
+    /// Turn the wallet into a `threshold`/N multisig wallet, where `multisig_info` holds the info
+    /// returned by [`Self::prepare_multisig`] of the N - 1 other participants.
+    ///
+    /// Unless the returned `multisig_info` is empty, the returned address is not the multisig
+    /// address yet: the key exchange then continues with [`Self::exchange_multisig_keys`] until it
+    /// returns the address.
+    pub async fn make_multisig(
+        &self,
+        multisig_info: Vec<String>,
+        threshold: u32,
+        password: String,
+    ) -> anyhow::Result<MultisigKeyExchangeData> {
+        let params = empty()
+            .chain(once(("multisig_info", multisig_info.into())))
+            .chain(once(("threshold", threshold.into())))
+            .chain(once(("password", password.into())));
+
+        let rsp = self
+            .inner
+            .request::<MultisigKeyExchangeData>("make_multisig", RpcParams::map(params))
+            .await?;
+        self.inner.check_addresses(&rsp.address)?;
+        Ok(rsp)
+    }
+
+    /// Run a round of the multisig key exchange, where `multisig_info` holds the info returned by
+    /// the other participants in the previous round.
+    pub async fn exchange_multisig_keys(
+        &self,
+        multisig_info: Vec<String>,
+        password: String,
+    ) -> anyhow::Result<MultisigKeyExchangeData> {
+        let params = empty()
+            .chain(once(("multisig_info", multisig_info.into())))
+            .chain(once(("password", password.into())));
+
+        let rsp = self
+            .inner
+            .request::<MultisigKeyExchangeData>("exchange_multisig_keys", RpcParams::map(params))
+            .await?;
+        self.inner.check_addresses(&rsp.address)?;
+        Ok(rsp)
+    }
+
+    /// Finish the key exchange of N-1/N multisig wallets older than v0.18; newer wallets finish it
+    /// with [`Self::exchange_multisig_keys`] and may reject this method.
+    pub async fn finalize_multisig(
+        &self,
+        multisig_info: Vec<String>,
+        password: String,
+    ) -> anyhow::Result<MultisigKeyExchangeData> {
+        let params = empty()
+            .chain(once(("multisig_info", multisig_info.into())))
+            .chain(once(("password", password.into())));
 
         let rsp = self
             .inner
-            .request::<Rsp>("check_tx_key", RpcParams::map(params))
+            .request::<MultisigKeyExchangeData>("finalize_multisig", RpcParams::map(params))
             .await?;
+        self.inner.check_addresses(&rsp.address)?;
+        Ok(rsp)
+    }
+
+    /// Export the outputs info of a multisig wallet, to import by the other participants with
+    /// [`Self::import_multisig_info`] before spending.
+    pub async fn export_multisig_info(&self) -> anyhow::Result<MultisigInfo> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            info: HashString<MultisigInfo>,
+        }
+
+        Ok(self
+            .inner
+            .request::<Rsp>("export_multisig_info", RpcParams::None)
+            .await?
+            .info
+            .0)
+    }
+
+    /// Import the outputs info exported by the other participants of a multisig wallet, and
+    /// return the number of outputs updated.
+    pub async fn import_multisig_info(&self, info: Vec<MultisigInfo>) -> anyhow::Result<u64> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            n_outputs: u64,
+        }
+
+        let params = once((
+            "info",
+            info.into_iter()
+                .map(|info| HashString(info).to_string())
+                .collect::<Vec<_>>()
+                .into(),
+        ));
+
+        Ok(self
+            .inner
+            .request::<Rsp>("import_multisig_info", RpcParams::map(params))
+            .await?
+            .n_outputs)
+    }
+
+    /// Add the signatures of this participant to transactions created by a multisig wallet, e.g.
+    /// the `multisig_txset` of [`TransferData`].
+    pub async fn sign_multisig(
+        &self,
+        tx_data_hex: MultisigTxSet,
+    ) -> anyhow::Result<SignMultisigData> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            tx_data_hex: HashString<MultisigTxSet>,
+            #[serde(default)]
+            tx_hash_list: Vec<HashString<CryptoNoteHash>>,
+        }
+
+        impl From<Rsp> for SignMultisigData {
+            fn from(value: Rsp) -> Self {
+                Self {
+                    tx_data_hex: value.tx_data_hex.0,
+                    tx_hash_list: value.tx_hash_list.into_iter().map(|v| v.0).collect(),
+                }
+            }
+        }
+
+        let params = once(("tx_data_hex", HashString(tx_data_hex).to_string().into()));
+
+        self.inner
+            .request::<Rsp>("sign_multisig", RpcParams::map(params))
+            .await
+            .map(From::from)
+    }
 
-        Ok((rsp.confirmations, rsp.in_pool, rsp.received))
+    /// Submit multisig transactions signed by enough participants.
+    pub async fn submit_multisig(
+        &self,
+        tx_data_hex: MultisigTxSet,
+    ) -> anyhow::Result<Vec<CryptoNoteHash>> {
+        #[derive(Deserialize)]
+        struct Rsp {
+            tx_hash_list: Vec<HashString<CryptoNoteHash>>,
+        }
+
+        let params = once(("tx_data_hex", HashString(tx_data_hex).to_string().into()));
+
+        self.inner
+            .request::<Rsp>("submit_multisig", RpcParams::map(params))
+            .await
+            .map(|v| v.tx_hash_list.into_iter().map(|v| v.0).collect())
     }
 
     /// Get RPC version Major & Minor integer-format, where Major is the first 16 bits and Minor
@@ -1152,7 +2347,7 @@ impl WalletClient {
 
         let version = self
             .inner
-            .request::<Rsp>("get_version", RpcParams::None)
+            .request_unchecked::<Rsp>("get_version", RpcParams::None)
             .await?;
 
         let major = version.version >> 16;
@@ -1215,6 +2410,81 @@ mod tests {
         assert_eq!(Params::from(rpc_param_map), Params::Map(serde_json_map));
     }
 
+    #[test]
+    fn split_range_in_chunks() {
+        assert_eq!(split_range(0..=9, 5), vec![0..=4, 5..=9]);
+        assert_eq!(split_range(0..=10, 5), vec![0..=4, 5..=9, 10..=10]);
+        assert_eq!(split_range(3..=3, 1000), vec![3..=3]);
+        assert_eq!(split_range(0..=2, 1), vec![0..=0, 1..=1, 2..=2]);
+        assert_eq!(
+            split_range(u64::MAX - 2..=u64::MAX, 2),
+            vec![u64::MAX - 2..=u64::MAX - 1, u64::MAX..=u64::MAX]
+        );
+        assert_eq!(
+            split_range(RangeInclusive::new(5, 4), 10),
+            vec![RangeInclusive::new(5, 4)]
+        );
+    }
+
+    #[test]
+    fn merge_chunked_transactions_responses() {
+        let tx = |byte| Transaction {
+            as_hex: format!("{:02x}", byte),
+            as_json: None,
+            block_height: Some(1),
+            block_timestamp: Some(1),
+            confirmations: Some(1),
+            double_spend_seen: false,
+            in_pool: false,
+            output_indices: Some(vec![]),
+            prunable_hash: None,
+            pruned_as_hex: None,
+            received_timestamp: None,
+            relayed: None,
+            tx_hash: HashString(CryptoNoteHash([byte; 32])),
+        };
+        let rsp = |txs: Vec<u8>, missed: Vec<u8>, untrusted| TransactionsResponse {
+            credits: 1,
+            top_hash: format!("{}", txs.len()),
+            status: "OK".to_string(),
+            missed_tx: Some(missed).filter(|m| !m.is_empty()).map(|m| {
+                m.into_iter()
+                    .map(|b| HashString(CryptoNoteHash([b; 32])))
+                    .collect()
+            }),
+            txs: Some(txs.iter().map(|b| tx(*b)).collect::<Vec<_>>()).filter(|t| !t.is_empty()),
+            txs_as_hex: Some(txs.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>())
+                .filter(|t| !t.is_empty()),
+            txs_as_json: None,
+            untrusted,
+        };
+
+        let merged = merge_transactions_responses(vec![
+            rsp(vec![1, 2], vec![], false),
+            rsp(vec![], vec![3, 4], true),
+            rsp(vec![5], vec![6], false),
+        ]);
+
+        assert_eq!(
+            merged,
+            TransactionsResponse {
+                credits: 3,
+                top_hash: "1".to_string(),
+                status: "OK".to_string(),
+                missed_tx: Some(
+                    [3, 4, 6]
+                        .iter()
+                        .map(|b| HashString(CryptoNoteHash([*b; 32])))
+                        .collect()
+                ),
+                txs: Some(vec![tx(1), tx(2), tx(5)]),
+                txs_as_hex: Some(vec!["01".to_string(), "02".to_string(), "05".to_string()]),
+                txs_as_json: None,
+                untrusted: true,
+            }
+        );
+    }
+
     #[test]
     fn serialize_transfer_type() {
         let transfer_types = vec![
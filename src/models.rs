@@ -20,15 +20,15 @@ use monero::{
         address::PaymentId,
         amount::{self, Amount},
     },
-    Address,
+    Address, Network,
 };
 use serde::{Deserialize, Deserializer, Serialize};
 use std::{collections::HashMap, num::NonZeroU64};
 
 macro_rules! hash_type {
-    ($name:ident, $len:expr) => {
+    ($(#[$attr:meta])* $name:ident, $len:expr) => {
         ::fixed_hash::construct_fixed_hash! {
-            /// Return type of daemon `on_get_block_hash`.
+            $(#[$attr])*
             #[derive(::serde::Serialize, ::serde::Deserialize)]
             pub struct $name($len);
         }
@@ -37,7 +37,96 @@ macro_rules! hash_type {
     };
 }
 
-hash_type!(BlockHash, 32);
+hash_type!(
+    /// Return type of daemon `on_get_block_hash`.
+    BlockHash,
+    32
+);
+hash_type!(
+    /// Key image of a spent output, as found in transaction inputs.
+    KeyImage,
+    32
+);
+hash_type!(
+    /// Raw 32 bytes RingCT key: one-time output keys, commitments and signature elements.
+    RctKey,
+    32
+);
+hash_type!(
+    /// Encrypted amount of an output, as found in compact `ecdhInfo` since `Bulletproof2`.
+    EncryptedAmount,
+    8
+);
+hash_type!(
+    /// View tag of an output, introduced with `Bulletproof+`.
+    ViewTag,
+    1
+);
+
+macro_rules! bytes_type {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+        pub struct $name(pub Vec<u8>);
+
+        impl $name {
+            /// Whether the value is empty, as returned by the wallet when it was not requested.
+            pub fn is_empty(&self) -> bool {
+                self.0.is_empty()
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl From<Vec<u8>> for $name {
+            fn from(v: Vec<u8>) -> Self {
+                Self(v)
+            }
+        }
+
+        impl HashType for $name {
+            fn from_str(v: &str) -> anyhow::Result<Self> {
+                Ok(Self(<Vec<u8> as HashType>::from_str(v)?))
+            }
+        }
+    };
+}
+
+bytes_type!(
+    /// Secret key of a transaction, followed by its additional keys when it has subaddress
+    /// destinations.
+    TxKey
+);
+bytes_type!(
+    /// Serialized transaction, as returned by wallet `transfer` and friends.
+    TxBlob
+);
+bytes_type!(
+    /// Transaction metadata, needed to relay a transaction created with `do_not_relay`.
+    TxMetadata
+);
+bytes_type!(
+    /// Unsigned transactions created by a view-only wallet, to sign with wallet `sign_transfer`.
+    UnsignedTxSet
+);
+bytes_type!(
+    /// Signed transactions returned by wallet `sign_transfer`, to submit with wallet
+    /// `submit_transfer`.
+    SignedTxSet
+);
+bytes_type!(
+    /// Transactions created by a multisig wallet, to sign by the other participants.
+    MultisigTxSet
+);
+bytes_type!(
+    /// Outputs info of a multisig participant, exported with wallet `export_multisig_info` and
+    /// imported by the other participants with `import_multisig_info` before every spend.
+    MultisigInfo
+);
 
 /// Helper type to unwrap RPC results.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -159,30 +248,360 @@ pub struct TransactionsResponse {
     pub missed_tx: Option<Vec<HashString<CryptoNoteHash>>>,
     pub txs: Option<Vec<Transaction>>,
     pub txs_as_hex: Option<Vec<String>>,
-    pub txs_as_json: Option<Vec<String>>, // needs to be parsed as JsonTransaction, but is received as a string
+    /// See [`Self::decode_txs_as_json`].
+    pub txs_as_json: Option<Vec<String>>,
+    pub untrusted: bool,
+}
+
+/// Return type of daemon RPC `get_transaction_pool_hashes`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionPoolHashesResponse {
+    pub credits: u64,
+    pub status: String,
+    pub top_hash: String,
+    /// Omitted by the daemon when the pool is empty.
+    #[serde(default)]
+    pub tx_hashes: Vec<HashString<CryptoNoteHash>>,
     pub untrusted: bool,
 }
 
+/// Return type of daemon RPC `get_transaction_pool_stats`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TxPoolStats {
+    pub bytes_max: u32,
+    pub bytes_med: u32,
+    pub bytes_min: u32,
+    pub bytes_total: u64,
+    #[serde(with = "amount::serde::as_pico")]
+    pub fee_total: Amount,
+    /// Number of transactions in the pool for more than 10 minutes.
+    pub num_10m: u32,
+    /// Number of transactions flagged as double spends.
+    pub num_double_spends: u32,
+    pub num_failing: u32,
+    pub num_not_relayed: u32,
+    /// Unix time of the oldest transaction in the pool.
+    pub oldest: u64,
+    pub txs_total: u32,
+}
+
 /// Sub-type of [`TransactionsResponse`]'s return type of daemon RPC `get_transactions`.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Transaction {
+    /// See [`Self::decode_as_hex`].
     pub as_hex: String,
-    pub as_json: Option<String>, // needs to be parsed as JsonTransaction, but is received as a string
+    /// See [`Self::decode_as_json`].
+    pub as_json: Option<String>,
     pub block_height: Option<u64>,
     pub block_timestamp: Option<u64>,
+    /// Only present if the transaction is not in the pool.
+    pub confirmations: Option<u64>,
     pub double_spend_seen: bool,
     pub in_pool: bool,
     pub output_indices: Option<Vec<u64>>,
+    /// Hash of the prunable part of the transaction, needed to compute the hash of a pruned
+    /// transaction.
+    #[serde(default, deserialize_with = "deserialize_empty_as_none")]
+    pub prunable_hash: Option<HashString<CryptoNoteHash>>,
+    /// Pruned transaction blob, only filled by the daemon when the transaction is split.
+    #[serde(default)]
+    pub pruned_as_hex: Option<String>,
+    /// Only present if the transaction is in the pool.
+    pub received_timestamp: Option<u64>,
+    /// Only present if the transaction is in the pool.
+    pub relayed: Option<bool>,
     pub tx_hash: HashString<CryptoNoteHash>,
 }
 
-/// Helper type to partially decode `as_json` string fields in other RPC return types.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+fn deserialize_empty_as_none<'de, D, T>(deserializer: D) -> Result<Option<HashString<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: HashType,
+{
+    match Option::<String>::deserialize(deserializer)?.as_deref() {
+        None | Some("") => Ok(None),
+        Some(s) => Ok(Some(HashString(
+            T::from_str(s).map_err(serde::de::Error::custom)?,
+        ))),
+    }
+}
+
+pub(crate) fn deserialize_empty_address_as_none<'de, D>(
+    deserializer: D,
+) -> Result<Option<Address>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)?.as_deref() {
+        None | Some("") => Ok(None),
+        Some(s) => s.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Decode a transaction blob, which may have been pruned by the daemon. A pruned transaction is
+/// returned without its prunable part, i.e. without `signatures` for version 1 transactions and
+/// with `rct_signatures.p` set to `None` for version 2 transactions.
+fn decode_transaction_blob(blob: &str) -> anyhow::Result<monero::Transaction> {
+    use monero::{
+        blockdata::transaction::TransactionPrefix,
+        consensus::encode::{self, Decodable},
+        util::ringct::{RctSig, RctSigBase},
+    };
+
+    let bytes = hex::decode(blob)?;
+
+    if let Ok(tx) = encode::deserialize::<monero::Transaction>(&bytes) {
+        return Ok(tx);
+    }
+
+    let mut reader = std::io::Cursor::new(&bytes[..]);
+    let prefix = TransactionPrefix::consensus_decode(&mut reader)?;
+    let sig = if *prefix.version == 1 || prefix.inputs.is_empty() {
+        None
+    } else {
+        RctSigBase::consensus_decode(&mut reader, prefix.inputs.len(), prefix.outputs.len())?
+    };
+
+    if reader.position() as usize != bytes.len() {
+        anyhow::bail!("Transaction blob is neither a full nor a pruned transaction");
+    }
+
+    Ok(monero::Transaction {
+        prefix,
+        signatures: vec![],
+        rct_signatures: RctSig { sig, p: None },
+    })
+}
+
+impl TransactionsResponse {
+    /// Decode every entry of `txs_as_json` into a [`JsonTransaction`]. Returns `None` if the
+    /// daemon was not asked to `decode_as_json`.
+    pub fn decode_txs_as_json(&self) -> anyhow::Result<Option<Vec<JsonTransaction>>> {
+        self.txs_as_json
+            .as_ref()
+            .map(|txs| {
+                txs.iter()
+                    .map(|tx| Ok(serde_json::from_str(tx)?))
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .transpose()
+    }
+
+    /// Decode every entry of `txs_as_hex` into a [`monero::Transaction`]. If the daemon was asked
+    /// to `prune`, the transactions are returned without their prunable part.
+    pub fn decode_txs_as_hex(&self) -> anyhow::Result<Option<Vec<monero::Transaction>>> {
+        self.txs_as_hex
+            .as_ref()
+            .map(|txs| {
+                txs.iter()
+                    .map(|tx| decode_transaction_blob(tx))
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .transpose()
+    }
+}
+
+impl Transaction {
+    /// Decode `as_hex`, or `pruned_as_hex` if the former is empty, into a [`monero::Transaction`].
+    /// Pruned transactions are returned without their prunable part; their hash can then only be
+    /// computed with `prunable_hash`.
+    pub fn decode_as_hex(&self) -> anyhow::Result<monero::Transaction> {
+        let blob = if self.as_hex.is_empty() {
+            self.pruned_as_hex.as_deref().unwrap_or_default()
+        } else {
+            &self.as_hex
+        };
+
+        if blob.is_empty() {
+            anyhow::bail!("Transaction has no blob to decode");
+        }
+
+        decode_transaction_blob(blob)
+    }
+
+    /// Decode `as_json` into a [`JsonTransaction`]. Returns `None` if the daemon was not asked
+    /// to `decode_as_json`, in which case it sends an empty string.
+    pub fn decode_as_json(&self) -> anyhow::Result<Option<JsonTransaction>> {
+        match self.as_json.as_deref() {
+            None | Some("") => Ok(None),
+            Some(json) => Ok(Some(serde_json::from_str(json)?)),
+        }
+    }
+}
+
+/// Decoded `as_json` and `txs_as_json` string fields of daemon RPC `get_transactions`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct JsonTransaction {
     pub version: u64,
     pub unlock_time: u64,
-    // TODO: these fields are skipped for now, their content changes often from hardfork to hardfork
-    // vin, vout, extra, rct_signatures, rct_sig_prunable
+    pub vin: Vec<JsonTxIn>,
+    pub vout: Vec<JsonTxOut>,
+    pub extra: Vec<u8>,
+    /// Ring signatures of version 1 transactions, one entry per input.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signatures: Vec<HashString<Vec<u8>>>,
+    /// RingCT signatures of version 2 transactions.
+    pub rct_signatures: Option<JsonRctSignatures>,
+    /// Prunable part of the RingCT signatures, absent for coinbase and pruned transactions.
+    pub rctsig_prunable: Option<JsonRctSigPrunable>,
+}
+
+/// Sub-type of [`JsonTransaction`]. Represent one transaction input.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonTxIn {
+    /// Coinbase input.
+    Gen { height: u64 },
+    /// Input spending a previous output.
+    Key {
+        #[serde(with = "amount::serde::as_pico")]
+        amount: Amount,
+        /// Relative offsets of the ring members.
+        key_offsets: Vec<u64>,
+        k_image: HashString<KeyImage>,
+    },
+}
+
+/// Sub-type of [`JsonTransaction`]. Represent one transaction output.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JsonTxOut {
+    /// Amount of the output, `0` for RingCT outputs.
+    #[serde(with = "amount::serde::as_pico")]
+    pub amount: Amount,
+    pub target: JsonTxOutTarget,
+}
+
+/// Sub-type of [`JsonTxOut`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonTxOutTarget {
+    /// One-time public key output.
+    Key(HashString<RctKey>),
+    /// One-time public key output with a view tag.
+    TaggedKey {
+        key: HashString<RctKey>,
+        view_tag: HashString<ViewTag>,
+    },
+}
+
+/// Sub-type of [`JsonTransaction`]. Non-prunable part of the RingCT signatures.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JsonRctSignatures {
+    /// RingCT type, `0` for coinbase transactions.
+    #[serde(rename = "type")]
+    pub rct_type: u8,
+    #[serde(
+        rename = "txnFee",
+        default,
+        with = "amount::serde::as_pico::opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub txn_fee: Option<Amount>,
+    /// Only present for the `Simple` RingCT type, later types put them in the prunable part.
+    #[serde(rename = "pseudoOuts", default, skip_serializing_if = "Vec::is_empty")]
+    pub pseudo_outs: Vec<HashString<RctKey>>,
+    #[serde(rename = "ecdhInfo", default, skip_serializing_if = "Vec::is_empty")]
+    pub ecdh_info: Vec<JsonEcdhInfo>,
+    #[serde(rename = "outPk", default, skip_serializing_if = "Vec::is_empty")]
+    pub out_pk: Vec<HashString<RctKey>>,
+}
+
+/// Sub-type of [`JsonRctSignatures`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum JsonEcdhInfo {
+    /// Format used before `Bulletproof2`.
+    Standard {
+        mask: HashString<RctKey>,
+        amount: HashString<RctKey>,
+    },
+    /// Format used since `Bulletproof2`.
+    Compact { amount: HashString<EncryptedAmount> },
+}
+
+/// Sub-type of [`JsonTransaction`]. Prunable part of the RingCT signatures; which fields are
+/// present depends on the RingCT type.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JsonRctSigPrunable {
+    /// Number of bulletproofs, present since `Bulletproof`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbp: Option<u64>,
+    #[serde(rename = "rangeSigs", default, skip_serializing_if = "Vec::is_empty")]
+    pub range_sigs: Vec<JsonRangeSig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bp: Vec<JsonBulletproof>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bpp: Vec<JsonBulletproofPlus>,
+    #[serde(rename = "MGs", default, skip_serializing_if = "Vec::is_empty")]
+    pub mgs: Vec<JsonMgSig>,
+    #[serde(rename = "CLSAGs", default, skip_serializing_if = "Vec::is_empty")]
+    pub clsags: Vec<JsonClsag>,
+    #[serde(rename = "pseudoOuts", default, skip_serializing_if = "Vec::is_empty")]
+    pub pseudo_outs: Vec<HashString<RctKey>>,
+}
+
+/// Sub-type of [`JsonRctSigPrunable`]. Borromean range signature, used before `Bulletproof`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JsonRangeSig {
+    pub asig: HashString<Vec<u8>>,
+    #[serde(rename = "Ci")]
+    pub ci: HashString<Vec<u8>>,
+}
+
+/// Sub-type of [`JsonRctSigPrunable`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JsonBulletproof {
+    #[serde(rename = "A")]
+    pub big_a: HashString<RctKey>,
+    #[serde(rename = "S")]
+    pub big_s: HashString<RctKey>,
+    #[serde(rename = "T1")]
+    pub t1: HashString<RctKey>,
+    #[serde(rename = "T2")]
+    pub t2: HashString<RctKey>,
+    pub taux: HashString<RctKey>,
+    pub mu: HashString<RctKey>,
+    #[serde(rename = "L")]
+    pub l: Vec<HashString<RctKey>>,
+    #[serde(rename = "R")]
+    pub r: Vec<HashString<RctKey>>,
+    pub a: HashString<RctKey>,
+    pub b: HashString<RctKey>,
+    pub t: HashString<RctKey>,
+}
+
+/// Sub-type of [`JsonRctSigPrunable`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JsonBulletproofPlus {
+    #[serde(rename = "A")]
+    pub a: HashString<RctKey>,
+    #[serde(rename = "A1")]
+    pub a1: HashString<RctKey>,
+    #[serde(rename = "B")]
+    pub b: HashString<RctKey>,
+    pub r1: HashString<RctKey>,
+    pub s1: HashString<RctKey>,
+    pub d1: HashString<RctKey>,
+    #[serde(rename = "L")]
+    pub l: Vec<HashString<RctKey>>,
+    #[serde(rename = "R")]
+    pub r: Vec<HashString<RctKey>>,
+}
+
+/// Sub-type of [`JsonRctSigPrunable`]. MLSAG signature, used before `CLSAG`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JsonMgSig {
+    pub ss: Vec<Vec<HashString<RctKey>>>,
+    pub cc: HashString<RctKey>,
+}
+
+/// Sub-type of [`JsonRctSigPrunable`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JsonClsag {
+    pub s: Vec<HashString<RctKey>>,
+    pub c1: HashString<RctKey>,
+    #[serde(rename = "D")]
+    pub d: HashString<RctKey>,
 }
 
 /// Sub-type of [`BalanceData`]'s return type of wallet `get_balance`.
@@ -230,11 +649,13 @@ pub struct TransferData {
     pub amount: Amount,
     #[serde(with = "amount::serde::as_pico")]
     pub fee: Amount,
-    pub tx_blob: HashString<Vec<u8>>,
+    /// Transaction to sign by the other participants, when sent from a multisig wallet.
+    pub multisig_txset: HashString<MultisigTxSet>,
+    pub tx_blob: HashString<TxBlob>,
     pub tx_hash: HashString<CryptoNoteHash>,
-    pub tx_key: HashString<Vec<u8>>,
-    pub tx_metadata: HashString<Vec<u8>>,
-    pub unsigned_txset: HashString<Vec<u8>>,
+    pub tx_key: HashString<TxKey>,
+    pub tx_metadata: HashString<TxMetadata>,
+    pub unsigned_txset: HashString<UnsignedTxSet>,
 }
 
 /// Sub-type of [`AddressData`]'s return type of wallet `get_address`.
@@ -297,36 +718,108 @@ pub struct IncomingTransfer {
     #[serde(with = "amount::serde::as_pico")]
     pub amount: Amount,
     pub global_index: u64,
-    pub key_image: Option<String>,
+    /// Missing when the key image is not known, e.g. in view-only wallets.
+    #[serde(default, deserialize_with = "deserialize_empty_as_none")]
+    pub key_image: Option<HashString<KeyImage>>,
     pub spent: bool,
+    /// Whether the output is frozen, see [`crate::WalletClient::freeze`]. Missing before wallet
+    /// v0.15.
+    pub frozen: Option<bool>,
+    /// Whether the output can be spent yet. Missing before wallet v0.15.
+    pub unlocked: Option<bool>,
     pub subaddr_index: subaddress::Index,
     pub tx_hash: HashString<CryptoNoteHash>,
     pub tx_size: Option<u64>,
 }
 
+/// Return type of wallet `transfer_split`, with one element per transaction in each list. The
+/// key, blob and metadata lists are empty unless requested in [`TransferOptions`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransferSplitData {
+    pub tx_hash_list: Vec<HashString<CryptoNoteHash>>,
+    #[serde(default)]
+    pub tx_key_list: Vec<HashString<TxKey>>,
+    #[serde(
+        default,
+        serialize_with = "amount::serde::as_pico::slice::serialize",
+        deserialize_with = "amount::serde::as_pico::vec::deserialize_amount"
+    )]
+    pub amount_list: Vec<Amount>,
+    #[serde(
+        default,
+        serialize_with = "amount::serde::as_pico::slice::serialize",
+        deserialize_with = "amount::serde::as_pico::vec::deserialize_amount"
+    )]
+    pub fee_list: Vec<Amount>,
+    #[serde(default)]
+    pub weight_list: Vec<u64>,
+    #[serde(default)]
+    pub tx_blob_list: Vec<HashString<TxBlob>>,
+    #[serde(default)]
+    pub tx_metadata_list: Vec<HashString<TxMetadata>>,
+    pub multisig_txset: HashString<MultisigTxSet>,
+    pub unsigned_txset: HashString<UnsignedTxSet>,
+}
+
+/// Options shared by wallet `sweep_all`, `sweep_single` and `sweep_dust`, and argument type of the
+/// latter.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SweepOptions {
+    pub get_tx_keys: Option<bool>,
+    pub do_not_relay: Option<bool>,
+    pub get_tx_hex: Option<bool>,
+    pub get_tx_metadata: Option<bool>,
+}
+
 /// Argument type of wallet `sweep_all`.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SweepAllArgs {
     pub address: Address,
     pub account_index: u32,
     pub subaddr_indices: Option<Vec<u32>>,
+    /// Sweep all the subaddresses of `account_index`, instead of `subaddr_indices`.
+    pub subaddr_indices_all: Option<bool>,
     pub priority: TransferPriority,
     pub mixin: u64,
     pub ring_size: u64,
     pub unlock_time: u64,
-    pub get_tx_keys: Option<bool>,
+    /// Number of outputs to create for `address`, instead of one.
+    pub outputs: Option<u64>,
     #[serde(default, with = "amount::serde::as_pico::opt")]
     pub below_amount: Option<Amount>,
-    pub do_not_relay: Option<bool>,
-    pub get_tx_hex: Option<bool>,
-    pub get_tx_metadata: Option<bool>,
+    #[serde(flatten)]
+    pub options: SweepOptions,
 }
 
-/// Return type of wallet `sweep_all`.
+/// Argument type of wallet `sweep_single`.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct SweepAllData {
+pub struct SweepSingleArgs {
+    pub address: Address,
+    /// Key image of the output to sweep.
+    pub key_image: HashString<KeyImage>,
+    pub priority: TransferPriority,
+    pub ring_size: u64,
+    pub unlock_time: u64,
+    /// Number of outputs to create for `address`, instead of one.
+    pub outputs: Option<u64>,
+    #[serde(flatten)]
+    pub options: SweepOptions,
+}
+
+/// Former name of [`SweepData`].
+#[deprecated(since = "0.3.0", note = "renamed to `SweepData`")]
+pub type SweepAllData = SweepData;
+
+/// Return type of wallet `sweep_all`, `sweep_single` and `sweep_dust`, with one element per
+/// transaction in each list. The key, blob and metadata lists are empty unless requested in
+/// [`SweepOptions`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SweepData {
+    #[serde(default)]
     pub tx_hash_list: Vec<HashString<CryptoNoteHash>>,
-    pub tx_key_list: Option<Vec<HashString<CryptoNoteHash>>>,
+    /// Secret transaction keys.
+    #[serde(default)]
+    pub tx_key_list: Vec<HashString<TxKey>>,
     #[serde(
         default,
         serialize_with = "amount::serde::as_pico::slice::serialize",
@@ -339,22 +832,34 @@ pub struct SweepAllData {
         deserialize_with = "amount::serde::as_pico::vec::deserialize_amount"
     )]
     pub fee_list: Vec<Amount>,
-    pub tx_blob_list: Option<Vec<String>>,
-    pub tx_metadata_list: Option<Vec<String>>,
-    pub multisig_txset: String,
-    pub unsigned_txset: String,
+    #[serde(default)]
+    pub weight_list: Vec<u64>,
+    #[serde(default)]
+    pub tx_blob_list: Vec<HashString<TxBlob>>,
+    #[serde(default)]
+    pub tx_metadata_list: Vec<HashString<TxMetadata>>,
+    pub multisig_txset: HashString<MultisigTxSet>,
+    pub unsigned_txset: HashString<UnsignedTxSet>,
 }
 
 /// Argument type of wallet `transfer`.
 #[derive(Clone, Debug, Default)]
 pub struct TransferOptions {
     pub account_index: Option<u32>,
+    /// Subaddresses of `account_index` to spend from.
     pub subaddr_indices: Option<Vec<u32>>,
     pub mixin: Option<u64>,
     pub ring_size: Option<u64>,
     pub unlock_time: Option<u64>,
     pub payment_id: Option<PaymentId>,
     pub do_not_relay: Option<bool>,
+    /// Indices of the destinations the fee is subtracted from, split evenly between them.
+    pub subtract_fee_from_outputs: Option<Vec<u32>>,
+    /// Defaults to `true`, and likewise for `get_tx_hex` and `get_tx_metadata`; the matching
+    /// fields of [`TransferData`] are empty when set to `false`.
+    pub get_tx_key: Option<bool>,
+    pub get_tx_hex: Option<bool>,
+    pub get_tx_metadata: Option<bool>,
 }
 
 /// Argument type of wallet `generate_from_keys`.
@@ -401,6 +906,50 @@ pub struct GetAccountsData {
     pub total_unlocked_balance: Amount,
 }
 
+/// Return sub-type of wallet `get_account_tags`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AccountTag {
+    pub tag: String,
+    /// Description of the tag, set with wallet `set_account_tag_description`.
+    pub label: String,
+    /// Indices of the accounts with this tag.
+    #[serde(default)]
+    pub accounts: Vec<u32>,
+}
+
+/// Return sub-type of wallet `get_address_book`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddressBookEntry {
+    pub index: u64,
+    /// Address of the entry, which is an integrated address when it has a payment id.
+    pub address: Address,
+    pub description: String,
+    /// Payment id of the integrated address.
+    pub payment_id: Option<PaymentId>,
+}
+
+/// Return type of wallet `split_integrated_address` and of
+/// [`split_integrated_address`](crate::split_integrated_address).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IntegratedAddressParts {
+    pub standard_address: Address,
+    pub payment_id: PaymentId,
+    pub is_subaddress: bool,
+}
+
+/// Return type of wallet `validate_address` and of
+/// [`validate_address`](crate::validate_address).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AddressValidation {
+    pub valid: bool,
+    pub integrated: bool,
+    pub subaddress: bool,
+    /// Network of the address; `None` when it is not valid.
+    pub nettype: Option<Network>,
+    /// Address the OpenAlias resolved to, when `allow_openalias` is set.
+    pub openalias_address: Option<Address>,
+}
+
 /// Monero uses two type of private key in its cryptographic system: (1) a view key, and (2) a
 /// spend key.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -529,9 +1078,112 @@ pub struct GotTransfer {
 /// Return type of wallet `sign_transfer`.
 #[derive(Clone, Debug)]
 pub struct SignedTransferOutput {
-    pub signed_txset: Vec<u8>,
+    pub signed_txset: SignedTxSet,
+    pub tx_hash_list: Vec<CryptoNoteHash>,
+    pub tx_raw_list: Vec<TxBlob>,
+}
+
+/// Return type of wallet `check_tx_key`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TxKeyCheck {
+    pub confirmations: u64,
+    pub in_pool: bool,
+    /// Amount received by the address in the transaction.
+    #[serde(with = "amount::serde::as_pico")]
+    pub received: Amount,
+}
+
+/// Return type of wallet `check_tx_proof`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TxProofCheck {
+    /// Whether the signature is valid.
+    pub good: bool,
+    pub confirmations: u64,
+    pub in_pool: bool,
+    /// Amount received by the address in the transaction.
+    #[serde(with = "amount::serde::as_pico")]
+    pub received: Amount,
+}
+
+/// Key used by wallet `sign`, and found by wallet `verify`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureType {
+    Spend,
+    View,
+}
+
+/// Return type of wallet `verify` and of [`verify_signature`](crate::verify_signature).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SignatureCheck {
+    /// Whether the signature is valid.
+    pub good: bool,
+    /// Version of the signature, i.e. 1 for `SigV1` and 2 for `SigV2`; 0 when it is not valid.
+    pub version: u32,
+    /// Whether the signature has an older format than the one made by the wallet.
+    pub old: bool,
+    /// Key that made the signature; `None` when it is not valid, or when the wallet is older than
+    /// v0.18 and does not return it.
+    pub signature_type: Option<SignatureType>,
+}
+
+/// Argument type of wallet `get_reserve_proof`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReserveProofAmount {
+    /// Prove the whole balance of the wallet.
+    All,
+    /// Prove at least `amount` of the balance of the account `account_index`.
+    Account { account_index: u32, amount: Amount },
+}
+
+/// Return type of wallet `check_reserve_proof`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ReserveProofCheck {
+    /// Whether the signature is valid.
+    pub good: bool,
+    /// Amount of the outputs in the proof.
+    #[serde(with = "amount::serde::as_pico")]
+    pub total: Amount,
+    /// Amount of the outputs in the proof that are already spent.
+    #[serde(with = "amount::serde::as_pico")]
+    pub spent: Amount,
+}
+
+/// Return type of wallet `is_multisig`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MultisigStatus {
+    /// Whether the wallet is multisig, even if the key exchange is not finished.
+    pub multisig: bool,
+    /// Whether the key exchange is finished, so that the wallet can be used.
+    pub ready: bool,
+    /// Number of signatures needed to spend, i.e. M in M/N.
+    pub threshold: u32,
+    /// Number of participants, i.e. N in M/N.
+    pub total: u32,
+}
+
+/// Return type of wallet `make_multisig`, `exchange_multisig_keys` and `finalize_multisig`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MultisigKeyExchangeData {
+    /// The multisig address once the key exchange is finished. `make_multisig` also returns the
+    /// intermediate address of the wallet while `multisig_info` is not empty, whereas
+    /// `exchange_multisig_keys` and `finalize_multisig` return `None` until the exchange is
+    /// finished.
+    #[serde(default, deserialize_with = "deserialize_empty_address_as_none")]
+    pub address: Option<Address>,
+    /// Info to send to the other participants for the next round of `exchange_multisig_keys`,
+    /// empty once the key exchange is finished.
+    #[serde(default)]
+    pub multisig_info: String,
+}
+
+/// Return type of wallet `sign_multisig`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignMultisigData {
+    /// The transactions with this participant's signatures added, to pass to the next signer or
+    /// to submit with wallet `submit_multisig`.
+    pub tx_data_hex: MultisigTxSet,
     pub tx_hash_list: Vec<CryptoNoteHash>,
-    pub tx_raw_list: Vec<Vec<u8>>,
 }
 
 /// Used to export and import signed key images. Return type of wallet `export_key_images` and
@@ -539,7 +1191,7 @@ pub struct SignedTransferOutput {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SignedKeyImage {
     /// The key image.
-    pub key_image: Vec<u8>,
+    pub key_image: KeyImage,
     /// Signature of the key image.
     pub signature: Vec<u8>,
 }
@@ -657,4 +1309,449 @@ mod tests {
         };
         assert_eq!(GenerateBlocksResponse::from(gbrr), expected_gbr);
     }
+
+    fn key_hex(byte: u8) -> String {
+        format!("{:02x}", byte).repeat(32)
+    }
+
+    #[test]
+    fn deserialize_json_transaction_coinbase() {
+        let json = format!(
+            r#"{{
+                "version": 2,
+                "unlock_time": 70,
+                "vin": [{{"gen": {{"height": 10}}}}],
+                "vout": [{{"amount": 35184338534400, "target": {{"tagged_key": {{"key": "{}", "view_tag": "a5"}}}}}}],
+                "extra": [1, 2, 3],
+                "rct_signatures": {{"type": 0}}
+            }}"#,
+            key_hex(1)
+        );
+
+        let tx: JsonTransaction = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            tx,
+            JsonTransaction {
+                version: 2,
+                unlock_time: 70,
+                vin: vec![JsonTxIn::Gen { height: 10 }],
+                vout: vec![JsonTxOut {
+                    amount: Amount::from_pico(35184338534400),
+                    target: JsonTxOutTarget::TaggedKey {
+                        key: HashString(RctKey::repeat_byte(1)),
+                        view_tag: HashString(ViewTag::repeat_byte(0xa5)),
+                    },
+                }],
+                extra: vec![1, 2, 3],
+                signatures: vec![],
+                rct_signatures: Some(JsonRctSignatures {
+                    rct_type: 0,
+                    txn_fee: None,
+                    pseudo_outs: vec![],
+                    ecdh_info: vec![],
+                    out_pk: vec![],
+                }),
+                rctsig_prunable: None,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_json_transaction_clsag_bulletproof_plus() {
+        let json = format!(
+            r#"{{
+                "version": 2,
+                "unlock_time": 0,
+                "vin": [{{"key": {{"amount": 0, "key_offsets": [100, 2, 3], "k_image": "{k}"}}}}],
+                "vout": [{{"amount": 0, "target": {{"key": "{k}"}}}}],
+                "extra": [],
+                "rct_signatures": {{
+                    "type": 6,
+                    "txnFee": 30720000,
+                    "ecdhInfo": [{{"amount": "0102030405060708"}}],
+                    "outPk": ["{k}"]
+                }},
+                "rctsig_prunable": {{
+                    "nbp": 1,
+                    "bpp": [{{"A": "{k}", "A1": "{k}", "B": "{k}", "r1": "{k}", "s1": "{k}", "d1": "{k}", "L": ["{k}"], "R": ["{k}"]}}],
+                    "CLSAGs": [{{"s": ["{k}", "{k}"], "c1": "{k}", "D": "{k}"}}],
+                    "pseudoOuts": ["{k}"]
+                }}
+            }}"#,
+            k = key_hex(2)
+        );
+
+        let tx: JsonTransaction = serde_json::from_str(&json).unwrap();
+        let key = HashString(RctKey::repeat_byte(2));
+
+        assert_eq!(
+            tx.vin,
+            vec![JsonTxIn::Key {
+                amount: Amount::from_pico(0),
+                key_offsets: vec![100, 2, 3],
+                k_image: HashString(KeyImage::repeat_byte(2)),
+            }]
+        );
+        assert_eq!(tx.vout[0].target, JsonTxOutTarget::Key(key.clone()));
+
+        let rct_signatures = tx.rct_signatures.unwrap();
+        assert_eq!(rct_signatures.rct_type, 6);
+        assert_eq!(rct_signatures.txn_fee, Some(Amount::from_pico(30720000)));
+        assert_eq!(
+            rct_signatures.ecdh_info,
+            vec![JsonEcdhInfo::Compact {
+                amount: HashString(EncryptedAmount::from([1, 2, 3, 4, 5, 6, 7, 8])),
+            }]
+        );
+        assert_eq!(rct_signatures.out_pk, vec![key.clone()]);
+
+        let prunable = tx.rctsig_prunable.unwrap();
+        assert_eq!(prunable.nbp, Some(1));
+        assert_eq!(prunable.bpp.len(), 1);
+        assert!(prunable.bp.is_empty());
+        assert!(prunable.mgs.is_empty());
+        assert_eq!(
+            prunable.clsags,
+            vec![JsonClsag {
+                s: vec![key.clone(), key.clone()],
+                c1: key.clone(),
+                d: key.clone(),
+            }]
+        );
+        assert_eq!(prunable.pseudo_outs, vec![key]);
+    }
+
+    #[test]
+    fn deserialize_json_ecdh_info_standard() {
+        let json = format!(
+            r#"{{"mask": "{}", "amount": "{}"}}"#,
+            key_hex(3),
+            key_hex(4)
+        );
+
+        assert_eq!(
+            serde_json::from_str::<JsonEcdhInfo>(&json).unwrap(),
+            JsonEcdhInfo::Standard {
+                mask: HashString(RctKey::repeat_byte(3)),
+                amount: HashString(RctKey::repeat_byte(4)),
+            }
+        );
+    }
+
+    #[test]
+    fn decode_as_json_for_transaction() {
+        let mut tx = Transaction {
+            as_hex: "".to_string(),
+            as_json: None,
+            block_height: None,
+            block_timestamp: None,
+            double_spend_seen: false,
+            in_pool: true,
+            output_indices: None,
+            tx_hash: HashString(CryptoNoteHash::zero()),
+            confirmations: None,
+            prunable_hash: None,
+            pruned_as_hex: None,
+            received_timestamp: None,
+            relayed: None,
+        };
+        assert_eq!(tx.decode_as_json().unwrap(), None);
+
+        tx.as_json = Some("".to_string());
+        assert_eq!(tx.decode_as_json().unwrap(), None);
+
+        tx.as_json = Some(
+            r#"{"version": 1, "unlock_time": 0, "vin": [], "vout": [], "extra": []}"#.to_string(),
+        );
+        let decoded = tx.decode_as_json().unwrap().unwrap();
+        assert_eq!(decoded.version, 1);
+        assert_eq!(decoded.rct_signatures, None);
+
+        tx.as_json = Some("{".to_string());
+        assert!(tx.decode_as_json().is_err());
+    }
+
+    #[test]
+    fn decode_txs_as_json_for_transactions_response() {
+        let mut rsp = TransactionsResponse {
+            credits: 0,
+            top_hash: "".to_string(),
+            status: "OK".to_string(),
+            missed_tx: None,
+            txs: None,
+            txs_as_hex: None,
+            txs_as_json: None,
+            untrusted: false,
+        };
+        assert_eq!(rsp.decode_txs_as_json().unwrap(), None);
+
+        rsp.txs_as_json = Some(vec![
+            r#"{"version": 2, "unlock_time": 0, "vin": [], "vout": [], "extra": [], "rct_signatures": {"type": 0}}"#.to_string(),
+        ]);
+        let decoded = rsp.decode_txs_as_json().unwrap().unwrap();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].version, 2);
+    }
+
+    fn coinbase_transaction() -> monero::Transaction {
+        use monero::{
+            blockdata::transaction::{RawExtraField, TransactionPrefix, TxIn, TxOut, TxOutTarget},
+            consensus::encode::VarInt,
+            util::ringct::{RctSig, RctSigBase, RctType},
+        };
+
+        monero::Transaction {
+            prefix: TransactionPrefix {
+                version: VarInt(2),
+                unlock_time: VarInt(70),
+                inputs: vec![TxIn::Gen { height: VarInt(10) }],
+                outputs: vec![TxOut {
+                    amount: VarInt(35184338534400),
+                    target: TxOutTarget::ToTaggedKey {
+                        key: [1; 32],
+                        view_tag: 0xa5,
+                    },
+                }],
+                extra: RawExtraField(vec![1, 2, 3]),
+            },
+            signatures: vec![],
+            rct_signatures: RctSig {
+                sig: Some(RctSigBase {
+                    rct_type: RctType::Null,
+                    txn_fee: Amount::from_pico(0),
+                    pseudo_outs: vec![],
+                    ecdh_info: vec![],
+                    out_pk: vec![],
+                }),
+                p: None,
+            },
+        }
+    }
+
+    fn pruned_transaction_blob() -> (String, monero::Transaction) {
+        use monero::{
+            blockdata::transaction::{
+                KeyImage as MoneroKeyImage, RawExtraField, TransactionPrefix, TxIn, TxOut,
+                TxOutTarget,
+            },
+            consensus::encode::{serialize, VarInt},
+            cryptonote::hash::Hash8,
+            util::ringct::{CtKey, EcdhInfo, Key, RctSig, RctSigBase, RctType},
+        };
+
+        let prefix = TransactionPrefix {
+            version: VarInt(2),
+            unlock_time: VarInt(0),
+            inputs: vec![TxIn::ToKey {
+                amount: VarInt(0),
+                key_offsets: vec![VarInt(100), VarInt(2)],
+                k_image: MoneroKeyImage {
+                    image: CryptoNoteHash([2; 32]),
+                },
+            }],
+            outputs: vec![TxOut {
+                amount: VarInt(0),
+                target: TxOutTarget::ToTaggedKey {
+                    key: [3; 32],
+                    view_tag: 1,
+                },
+            }],
+            extra: RawExtraField(vec![]),
+        };
+        let sig = RctSigBase {
+            rct_type: RctType::BulletproofPlus,
+            txn_fee: Amount::from_pico(30720000),
+            pseudo_outs: vec![],
+            ecdh_info: vec![EcdhInfo::Bulletproof {
+                amount: Hash8([4; 8]),
+            }],
+            out_pk: vec![CtKey {
+                mask: Key { key: [5; 32] },
+            }],
+        };
+
+        let mut bytes = serialize(&prefix);
+        bytes.extend(serialize(&sig));
+
+        let tx = monero::Transaction {
+            prefix,
+            signatures: vec![],
+            rct_signatures: RctSig {
+                sig: Some(sig),
+                p: None,
+            },
+        };
+
+        (hex::encode(bytes), tx)
+    }
+
+    #[test]
+    fn decode_as_hex_for_transaction() {
+        let coinbase = coinbase_transaction();
+        let (pruned_hex, pruned) = pruned_transaction_blob();
+
+        let mut tx = Transaction {
+            as_hex: monero::consensus::encode::serialize_hex(&coinbase),
+            as_json: None,
+            block_height: Some(10),
+            block_timestamp: Some(0),
+            confirmations: Some(1),
+            double_spend_seen: false,
+            in_pool: false,
+            output_indices: Some(vec![0]),
+            prunable_hash: None,
+            pruned_as_hex: Some("".to_string()),
+            received_timestamp: None,
+            relayed: None,
+            tx_hash: HashString(CryptoNoteHash::zero()),
+        };
+        assert_eq!(tx.decode_as_hex().unwrap(), coinbase);
+
+        tx.as_hex = pruned_hex.clone();
+        assert_eq!(tx.decode_as_hex().unwrap(), pruned);
+
+        tx.as_hex = "".to_string();
+        tx.pruned_as_hex = Some(pruned_hex.clone());
+        assert_eq!(tx.decode_as_hex().unwrap(), pruned);
+
+        tx.pruned_as_hex = None;
+        assert!(tx.decode_as_hex().is_err());
+
+        tx.as_hex = format!("{}00", pruned_hex);
+        assert!(tx.decode_as_hex().is_err());
+
+        tx.as_hex = "0x".to_string();
+        assert!(tx.decode_as_hex().is_err());
+    }
+
+    #[test]
+    fn decode_txs_as_hex_for_transactions_response() {
+        let coinbase = coinbase_transaction();
+        let (pruned_hex, pruned) = pruned_transaction_blob();
+
+        let mut rsp = TransactionsResponse {
+            credits: 0,
+            top_hash: "".to_string(),
+            status: "OK".to_string(),
+            missed_tx: None,
+            txs: None,
+            txs_as_hex: None,
+            txs_as_json: None,
+            untrusted: false,
+        };
+        assert_eq!(rsp.decode_txs_as_hex().unwrap(), None);
+
+        rsp.txs_as_hex = Some(vec![
+            monero::consensus::encode::serialize_hex(&coinbase),
+            pruned_hex,
+        ]);
+        assert_eq!(
+            rsp.decode_txs_as_hex().unwrap(),
+            Some(vec![coinbase, pruned])
+        );
+    }
+
+    #[test]
+    fn deserialize_transaction_prunable_hash() {
+        let json = r#"{
+            "as_hex": "",
+            "as_json": "",
+            "double_spend_seen": false,
+            "in_pool": true,
+            "prunable_hash": "",
+            "pruned_as_hex": "",
+            "received_timestamp": 1660000000,
+            "relayed": true,
+            "tx_hash": "0000000000000000000000000000000000000000000000000000000000000000"
+        }"#;
+        let tx: Transaction = serde_json::from_str(json).unwrap();
+        assert_eq!(tx.prunable_hash, None);
+        assert_eq!(tx.received_timestamp, Some(1660000000));
+        assert_eq!(tx.relayed, Some(true));
+
+        let json = json.replace(
+            r#""prunable_hash": """#,
+            &format!(r#""prunable_hash": "{}""#, "fa".repeat(32)),
+        );
+        let tx: Transaction = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            tx.prunable_hash,
+            Some(HashString(CryptoNoteHash([250; 32])))
+        );
+    }
+
+    #[test]
+    fn deserialize_transaction_pool_hashes_response() {
+        let json = r#"{"credits": 0, "status": "OK", "top_hash": "", "untrusted": false}"#;
+        let rsp: TransactionPoolHashesResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(rsp.tx_hashes, vec![]);
+
+        let json = format!(
+            r#"{{"credits": 0, "status": "OK", "top_hash": "", "tx_hashes": ["{}"], "untrusted": false}}"#,
+            "fa".repeat(32)
+        );
+        let rsp: TransactionPoolHashesResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(rsp.tx_hashes, vec![HashString(CryptoNoteHash([250; 32]))]);
+    }
+
+    #[test]
+    fn deserialize_multisig_key_exchange_data() {
+        let data: MultisigKeyExchangeData =
+            serde_json::from_str(r#"{"address": "", "multisig_info": "MultisigxV2Rn1"}"#).unwrap();
+        assert_eq!(
+            data,
+            MultisigKeyExchangeData {
+                address: None,
+                multisig_info: "MultisigxV2Rn1".to_string(),
+            }
+        );
+
+        let address = "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge";
+        let data: MultisigKeyExchangeData =
+            serde_json::from_str(&format!(r#"{{"address": "{}"}}"#, address)).unwrap();
+        assert_eq!(data.address, Some(address.parse().unwrap()));
+        assert!(data.multisig_info.is_empty());
+    }
+
+    #[test]
+    fn deserialize_transfer_split_data() {
+        let json = format!(
+            r#"{{"amount_list": [10, 20], "fee_list": [1, 2], "multisig_txset": "", "spent_key_images_list": [], "tx_hash_list": ["{}", "{}"], "unsigned_txset": "", "weight_list": [1500, 1600]}}"#,
+            "fa".repeat(32),
+            "fb".repeat(32)
+        );
+        let data: TransferSplitData = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            data.amount_list,
+            vec![Amount::from_pico(10), Amount::from_pico(20)]
+        );
+        assert_eq!(
+            data.fee_list,
+            vec![Amount::from_pico(1), Amount::from_pico(2)]
+        );
+        assert_eq!(data.weight_list, vec![1500, 1600]);
+        assert_eq!(data.tx_hash_list[1], HashString(CryptoNoteHash([251; 32])));
+        assert!(data.tx_key_list.is_empty());
+        assert!(data.tx_blob_list.is_empty());
+        assert!(data.unsigned_txset.0.is_empty());
+    }
+
+    #[test]
+    fn deserialize_sweep_data() {
+        // the wallet omits empty lists
+        let json = format!(
+            r#"{{"amount_list": [10], "fee_list": [1], "multisig_txset": "", "tx_hash_list": ["{}"], "unsigned_txset": "", "weight_list": [1500]}}"#,
+            "fa".repeat(32)
+        );
+        let data: SweepData = serde_json::from_str(&json).unwrap();
+        assert_eq!(data.amount_list, vec![Amount::from_pico(10)]);
+        assert_eq!(
+            data.tx_hash_list,
+            vec![HashString(CryptoNoteHash([250; 32]))]
+        );
+        assert!(data.tx_key_list.is_empty());
+        assert!(data.tx_metadata_list.is_empty());
+        assert!(data.multisig_txset.0.is_empty());
+    }
 }
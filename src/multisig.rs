@@ -0,0 +1,305 @@
+// Copyright 2019-2022 Artem Vorotnikov and Monero Rust Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{MultisigKeyExchangeData, TransferOptions, TransferPriority, WalletClient};
+use anyhow::Context;
+use monero::{cryptonote::hash::Hash as CryptoNoteHash, Address};
+use std::future::Future;
+
+/// Items of all the participants but `participant`.
+fn others<T: Clone>(items: &[T], participant: usize) -> Vec<T> {
+    items
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != participant)
+        .map(|(_, item)| item.clone())
+        .collect()
+}
+
+/// Run rounds of `exchange` on the participants whose key exchange is not finished, starting from
+/// the results of `make_multisig`, and return the multisig address once all of them agree on it.
+///
+/// `exchange` is called with the index of a participant and the info of the others from the
+/// previous round.
+pub(crate) async fn exchange_keys<F, Fut>(
+    mut round: Vec<MultisigKeyExchangeData>,
+    max_rounds: usize,
+    mut exchange: F,
+) -> anyhow::Result<Address>
+where
+    F: FnMut(usize, Vec<String>) -> Fut,
+    Fut: Future<Output = anyhow::Result<MultisigKeyExchangeData>>,
+{
+    // `make_multisig` returns the intermediate address of the wallet, unless it has no info left
+    // to exchange, i.e. for N/N multisig with wallets older than v0.18
+    for data in &mut round {
+        if !data.multisig_info.is_empty() {
+            data.address = None;
+        }
+    }
+
+    for _ in 0..=max_rounds {
+        if let Some(address) = round[0].address {
+            if round.iter().all(|data| data.address.is_some()) {
+                if round.iter().any(|data| data.address != Some(address)) {
+                    anyhow::bail!("Participants disagree on the multisig address");
+                }
+                return Ok(address);
+            }
+        }
+
+        let infos = round
+            .iter()
+            .map(|data| data.multisig_info.clone())
+            .collect::<Vec<_>>();
+        let mut next = Vec::with_capacity(round.len());
+        for (i, data) in round.into_iter().enumerate() {
+            if data.address.is_some() {
+                // the info of a finished participant is not needed anymore
+                next.push(MultisigKeyExchangeData {
+                    multisig_info: String::new(),
+                    ..data
+                });
+                continue;
+            }
+
+            let infos = others(&infos, i)
+                .into_iter()
+                .filter(|info| !info.is_empty())
+                .collect();
+            next.push(
+                exchange(i, infos)
+                    .await
+                    .with_context(|| format!("Key exchange failed for participant {}", i))?,
+            );
+        }
+        round = next;
+    }
+
+    anyhow::bail!(
+        "Multisig key exchange did not finish after {} rounds",
+        max_rounds
+    )
+}
+
+/// Drive an M/N multisig wallet whose N participants are [`WalletClient`]s, e.g. one per
+/// wallet-rpc instance, each with its own wallet opened.
+///
+/// The coordinator moves the info between the participants in the right order, for the key
+/// exchange with [`Self::setup`], for the export and import of outputs info needed before every
+/// spend with [`Self::sync`], and for the create, sign and submit cycle with [`Self::transfer`].
+#[derive(Clone, Debug)]
+pub struct MultisigCoordinator {
+    wallets: Vec<WalletClient>,
+    threshold: u32,
+}
+
+impl MultisigCoordinator {
+    /// Create a coordinator of `threshold`/N multisig, where N is the number of `wallets`.
+    /// Fails unless `2 <= threshold <= N`.
+    pub fn new(wallets: Vec<WalletClient>, threshold: u32) -> anyhow::Result<Self> {
+        if threshold < 2 || threshold as usize > wallets.len() {
+            anyhow::bail!("Invalid multisig threshold {}/{}", threshold, wallets.len());
+        }
+
+        Ok(Self { wallets, threshold })
+    }
+
+    /// The participants, in the order used by the signer indices of [`Self::transfer`].
+    pub fn wallets(&self) -> &[WalletClient] {
+        &self.wallets
+    }
+
+    /// Number of signatures needed to spend.
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// Turn the opened wallets, which must be new non-multisig wallets protected by `password`,
+    /// into the multisig wallet, and return its address.
+    ///
+    /// See [`WalletClient::prepare_multisig`] for `enable_multisig_experimental`.
+    pub async fn setup(
+        &self,
+        password: String,
+        enable_multisig_experimental: bool,
+    ) -> anyhow::Result<Address> {
+        let mut infos = vec![];
+        for wallet in &self.wallets {
+            infos.push(
+                wallet
+                    .prepare_multisig(enable_multisig_experimental)
+                    .await?,
+            );
+        }
+
+        let mut round = vec![];
+        for (i, wallet) in self.wallets.iter().enumerate() {
+            round.push(
+                wallet
+                    .make_multisig(others(&infos, i), self.threshold, password.clone())
+                    .await?,
+            );
+        }
+
+        exchange_keys(round, self.wallets.len(), |i, infos| {
+            self.wallets[i].exchange_multisig_keys(infos, password.clone())
+        })
+        .await
+    }
+
+    /// Export the outputs info of every participant and import it into the others. This is
+    /// needed after receiving funds and after every spend, before the next one.
+    pub async fn sync(&self) -> anyhow::Result<()> {
+        let mut infos = vec![];
+        for wallet in &self.wallets {
+            infos.push(wallet.export_multisig_info().await?);
+        }
+
+        for (i, wallet) in self.wallets.iter().enumerate() {
+            wallet.import_multisig_info(others(&infos, i)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Sync the participants, create a transfer from the first of `signers`, add the signatures
+    /// of the next ones, and submit it from the last one. `signers` are the indices in
+    /// [`Self::wallets`] of exactly `threshold` distinct participants.
+    ///
+    /// The wallets must be refreshed beforehand.
+    pub async fn transfer(
+        &self,
+        signers: &[usize],
+        destinations: Vec<(Address, monero::Amount)>,
+        priority: TransferPriority,
+        options: TransferOptions,
+    ) -> anyhow::Result<Vec<CryptoNoteHash>> {
+        if signers.len() != self.threshold as usize {
+            anyhow::bail!("Expected {} signers, got {}", self.threshold, signers.len());
+        }
+        for (i, signer) in signers.iter().enumerate() {
+            if *signer >= self.wallets.len() || signers[..i].contains(signer) {
+                anyhow::bail!("Invalid signer {}", signer);
+            }
+        }
+
+        self.sync().await?;
+
+        let mut tx_data_hex = self.wallets[signers[0]]
+            .transfer(destinations, priority, options)
+            .await?
+            .multisig_txset
+            .0;
+        for signer in &signers[1..] {
+            tx_data_hex = self.wallets[*signer]
+                .sign_multisig(tx_data_hex)
+                .await?
+                .tx_data_hex;
+        }
+
+        self.wallets[signers[signers.len() - 1]]
+            .submit_multisig(tx_data_hex)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_address;
+    use monero::Network;
+    use std::sync::Mutex;
+
+    fn address(i: u8) -> Address {
+        test_address(i, Network::Mainnet)
+    }
+
+    fn data(address: Option<Address>, multisig_info: &str) -> MultisigKeyExchangeData {
+        MultisigKeyExchangeData {
+            address,
+            multisig_info: multisig_info.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn exchange_keys_rounds() {
+        // 2/3 multisig: two rounds after make_multisig, which returns intermediate addresses
+        let calls = Mutex::new(vec![]);
+        let round = vec![
+            data(Some(address(2)), "a0"),
+            data(Some(address(3)), "b0"),
+            data(Some(address(4)), "c0"),
+        ];
+        let res = exchange_keys(round, 3, |i, infos| {
+            calls.lock().unwrap().push((i, infos.clone()));
+            let round = infos[0].chars().nth(1).unwrap();
+            let res = if round == '0' {
+                data(None, &format!("{}1", ["a", "b", "c"][i]))
+            } else {
+                data(Some(address(1)), "")
+            };
+            futures::future::ready(Ok(res))
+        })
+        .await;
+
+        assert_eq!(res.unwrap(), address(1));
+        let s = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            calls.into_inner().unwrap(),
+            vec![
+                (0, s(&["b0", "c0"])),
+                (1, s(&["a0", "c0"])),
+                (2, s(&["a0", "b0"])),
+                (0, s(&["b1", "c1"])),
+                (1, s(&["a1", "c1"])),
+                (2, s(&["a1", "b1"])),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn exchange_keys_failures() {
+        // N/N multisig finished by make_multisig on older wallets
+        let round = vec![data(Some(address(1)), ""), data(Some(address(1)), "")];
+        let res = exchange_keys(round, 2, |_, _| async { unreachable!() }).await;
+        assert_eq!(res.unwrap(), address(1));
+
+        let round = vec![data(Some(address(1)), ""), data(Some(address(2)), "")];
+        let err = exchange_keys(round, 2, |_, _| async { unreachable!() })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Participants disagree on the multisig address"
+        );
+
+        let round = vec![data(None, "a"), data(None, "b")];
+        let err = exchange_keys(round, 2, |_, _| async { Ok(data(None, "c")) })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Multisig key exchange did not finish after 2 rounds"
+        );
+
+        let round = vec![data(None, "a"), data(None, "b")];
+        let err = exchange_keys(round, 2, |_, _| async {
+            Err(anyhow::anyhow!("Server error: bad info"))
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Key exchange failed for participant 0");
+    }
+}
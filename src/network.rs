@@ -0,0 +1,133 @@
+// Copyright 2019-2022 Artem Vorotnikov and Monero Rust Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::check_address_network;
+use monero::{Address, Network};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Kind of server a bound client talks to, which tells how to find its network.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Server {
+    /// Network of the daemon `get_info` nettype.
+    Daemon,
+    /// Network of the primary address of the opened wallet.
+    Wallet,
+}
+
+/// Network a client is bound to with [`RpcClient::with_network`](crate::RpcClient::with_network),
+/// and whether its server was found on that network. Clones of a client share the check.
+#[derive(Clone, Debug)]
+pub(crate) struct NetworkBinding {
+    network: Network,
+    server: Server,
+    checked: Arc<AtomicBool>,
+}
+
+impl NetworkBinding {
+    pub(crate) fn new(network: Network, server: Server) -> Self {
+        Self {
+            network,
+            server,
+            checked: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub(crate) fn network(&self) -> Network {
+        self.network
+    }
+
+    pub(crate) fn server(&self) -> Server {
+        self.server
+    }
+
+    pub(crate) fn is_checked(&self) -> bool {
+        self.checked.load(Ordering::Acquire)
+    }
+
+    /// Check the server again on next use, e.g. after opening another wallet.
+    pub(crate) fn reset(&self) {
+        self.checked.store(false, Ordering::Release);
+    }
+
+    /// Record the network of the server, or fail if it is not the bound one.
+    pub(crate) fn check_server(&self, network: Option<Network>) -> anyhow::Result<()> {
+        let name = match self.server {
+            Server::Daemon => "daemon",
+            Server::Wallet => "wallet",
+        };
+        match network {
+            Some(network) if network == self.network => {
+                self.checked.store(true, Ordering::Release);
+                Ok(())
+            }
+            Some(network) => anyhow::bail!(
+                "Client bound to {:?}, but the {} is on {:?}",
+                self.network,
+                name,
+                network
+            ),
+            None => anyhow::bail!(
+                "Client bound to {:?}, but the network of the {} is unknown",
+                self.network,
+                name
+            ),
+        }
+    }
+
+    /// Fail unless all of `addresses` are addresses of the bound network.
+    pub(crate) fn check_addresses<'a>(
+        &self,
+        addresses: impl IntoIterator<Item = &'a Address>,
+    ) -> anyhow::Result<()> {
+        addresses
+            .into_iter()
+            .try_for_each(|address| check_address_network(address, self.network))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_key_pair;
+
+    #[test]
+    fn network_binding_checks() {
+        let binding = NetworkBinding::new(Network::Stagenet, Server::Wallet);
+        let clone = binding.clone();
+        assert!(!clone.is_checked());
+
+        assert_eq!(
+            binding
+                .check_server(Some(Network::Mainnet))
+                .unwrap_err()
+                .to_string(),
+            "Client bound to Stagenet, but the wallet is on Mainnet"
+        );
+        assert!(binding.check_server(None).is_err());
+        assert!(!clone.is_checked());
+        binding.check_server(Some(Network::Stagenet)).unwrap();
+        assert!(clone.is_checked());
+        clone.reset();
+        assert!(!binding.is_checked());
+
+        let key_pair = test_key_pair(1);
+        let stagenet = Address::from_keypair(Network::Stagenet, &key_pair);
+        let mainnet = Address::from_keypair(Network::Mainnet, &key_pair);
+        assert!(binding.check_addresses(&[stagenet, stagenet]).is_ok());
+        assert!(binding.check_addresses(&[stagenet, mainnet]).is_err());
+    }
+}
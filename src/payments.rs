@@ -0,0 +1,655 @@
+// Copyright 2019-2022 Artem Vorotnikov and Monero Rust Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    streams::poll_stream, BlockHeightFilter, GetTransfersCategory, GetTransfersSelector,
+    GotTransfer, HashString, TransferHeight, WalletClient,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use futures::{
+    future::{self, BoxFuture, FutureExt},
+    Future, Stream,
+};
+use monero::{
+    cryptonote::subaddress,
+    util::amount::{self, Amount},
+    Address,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Status of an [`Invoice`]. Only payments with enough confirmations count.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum InvoiceStatus {
+    /// Nothing received yet.
+    Unpaid,
+    /// Less than the expected amount received, before expiry.
+    PartiallyPaid,
+    /// The expected amount was received before expiry.
+    Paid,
+    /// More than the expected amount was received before expiry.
+    Overpaid,
+    /// Less than the expected amount was received before expiry. Payments are still tracked
+    /// during the grace period of the [`PaymentProcessor`], in case the missing amount arrives
+    /// late.
+    Expired,
+    /// The expected amount, or more, was received, but partly after expiry.
+    PaidLate,
+    /// Less than the expected amount was received by the end of the grace period, with nothing
+    /// left pending. Payments are no longer tracked.
+    Closed,
+}
+
+impl InvoiceStatus {
+    /// Whether payments are no longer tracked.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            InvoiceStatus::Paid
+                | InvoiceStatus::Overpaid
+                | InvoiceStatus::PaidLate
+                | InvoiceStatus::Closed
+        )
+    }
+}
+
+/// Invoice paid to its own subaddress, see [`PaymentProcessor`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Invoice {
+    pub id: String,
+    pub address: Address,
+    pub subaddr_index: subaddress::Index,
+    #[serde(with = "amount::serde::as_pico")]
+    pub amount: Amount,
+    pub created_at: DateTime<Utc>,
+    /// Wallet height when the invoice was created; payments are mined at this height or above.
+    pub created_height: u64,
+    pub expires_at: DateTime<Utc>,
+    pub status: InvoiceStatus,
+    /// Sum of the payments with enough confirmations.
+    #[serde(with = "amount::serde::as_pico")]
+    pub received: Amount,
+    /// Sum of the payments in the pool or without enough confirmations.
+    #[serde(with = "amount::serde::as_pico")]
+    pub pending: Amount,
+    pub payments: Vec<InvoicePayment>,
+}
+
+/// Sub-type of [`Invoice`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct InvoicePayment {
+    pub txid: HashString<Vec<u8>>,
+    #[serde(with = "amount::serde::as_pico")]
+    pub amount: Amount,
+    /// `0` while in the pool.
+    pub confirmations: u64,
+    /// When the payment was first seen, in the pool or in a block; used to detect late payments.
+    pub first_seen: DateTime<Utc>,
+}
+
+impl Invoice {
+    /// Replace the payments with the incoming `transfers` to the invoice subaddress, update the
+    /// status and return the previous one if it changed.
+    pub(crate) fn update(
+        &mut self,
+        transfers: &[GotTransfer],
+        confirmations: u64,
+        grace_period: ChronoDuration,
+        now: DateTime<Utc>,
+    ) -> Option<InvoiceStatus> {
+        let mut payments: Vec<InvoicePayment> = vec![];
+        for transfer in transfers {
+            let first_seen = self
+                .payments
+                .iter()
+                .find(|p| p.txid == transfer.txid)
+                .map_or(transfer.timestamp, |p| p.first_seen);
+            let transfer_confirmations = match transfer.height {
+                TransferHeight::InPool => 0,
+                TransferHeight::Confirmed(_) => transfer.confirmations.unwrap_or_default(),
+            };
+
+            // a transaction can hold several outputs to the same subaddress
+            match payments.iter_mut().find(|p| p.txid == transfer.txid) {
+                Some(payment) => payment.amount += transfer.amount,
+                None => payments.push(InvoicePayment {
+                    txid: transfer.txid.clone(),
+                    amount: transfer.amount,
+                    confirmations: transfer_confirmations,
+                    first_seen,
+                }),
+            }
+        }
+        self.payments = payments;
+
+        let sum = |payments: &[InvoicePayment], f: &dyn Fn(&InvoicePayment) -> bool| {
+            payments
+                .iter()
+                .filter(|p| f(p))
+                .fold(Amount::ZERO, |sum, p| sum + p.amount)
+        };
+        let expires_at = self.expires_at;
+        self.received = sum(&self.payments, &|p| p.confirmations >= confirmations);
+        self.pending = sum(&self.payments, &|p| p.confirmations < confirmations);
+        let received_in_time = sum(&self.payments, &|p| {
+            p.confirmations >= confirmations && p.first_seen <= expires_at
+        });
+
+        let status = if self.received >= self.amount {
+            if received_in_time < self.amount {
+                InvoiceStatus::PaidLate
+            } else if self.received > self.amount {
+                InvoiceStatus::Overpaid
+            } else {
+                InvoiceStatus::Paid
+            }
+        } else if now > self.expires_at + grace_period && self.pending == Amount::ZERO {
+            InvoiceStatus::Closed
+        } else if now > self.expires_at {
+            InvoiceStatus::Expired
+        } else if self.received > Amount::ZERO {
+            InvoiceStatus::PartiallyPaid
+        } else {
+            InvoiceStatus::Unpaid
+        };
+
+        if status == self.status {
+            return None;
+        }
+        Some(std::mem::replace(&mut self.status, status))
+    }
+}
+
+/// Storage of the invoices of a [`PaymentProcessor`].
+pub trait InvoiceStore {
+    fn get<'a>(&'a self, id: &'a str) -> BoxFuture<'a, anyhow::Result<Option<Invoice>>>;
+    /// Insert or replace an invoice.
+    fn save(&self, invoice: Invoice) -> BoxFuture<'_, anyhow::Result<()>>;
+    /// Invoices whose status is not final, see [`InvoiceStatus::is_final`].
+    fn open_invoices(&self) -> BoxFuture<'_, anyhow::Result<Vec<Invoice>>>;
+}
+
+/// In-memory [`InvoiceStore`], lost on restart.
+#[derive(Debug, Default)]
+pub struct InMemoryInvoiceStore {
+    invoices: Mutex<HashMap<String, Invoice>>,
+}
+
+impl InvoiceStore for InMemoryInvoiceStore {
+    fn get<'a>(&'a self, id: &'a str) -> BoxFuture<'a, anyhow::Result<Option<Invoice>>> {
+        let invoice = self.invoices.lock().unwrap().get(id).cloned();
+        future::ready(Ok(invoice)).boxed()
+    }
+
+    fn save(&self, invoice: Invoice) -> BoxFuture<'_, anyhow::Result<()>> {
+        self.invoices
+            .lock()
+            .unwrap()
+            .insert(invoice.id.clone(), invoice);
+        future::ready(Ok(())).boxed()
+    }
+
+    fn open_invoices(&self) -> BoxFuture<'_, anyhow::Result<Vec<Invoice>>> {
+        let mut invoices: Vec<_> = self
+            .invoices
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|invoice| !invoice.status.is_final())
+            .cloned()
+            .collect();
+        invoices.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        future::ready(Ok(invoices)).boxed()
+    }
+}
+
+/// Item type of [`PaymentProcessor::poll`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceEvent {
+    pub previous_status: InvoiceStatus,
+    pub invoice: Invoice,
+}
+
+/// Merchant payment processor: each invoice gets its own subaddress in `account_index`, and
+/// incoming transfers are matched to invoices by subaddress index.
+#[derive(Debug)]
+pub struct PaymentProcessor<S> {
+    wallet: WalletClient,
+    store: S,
+    account_index: u32,
+    confirmations: u64,
+    grace_period: ChronoDuration,
+}
+
+impl<S: InvoiceStore> PaymentProcessor<S> {
+    /// Payments count once they have `confirmations`. Expired invoices are tracked for
+    /// `grace_period` after expiry, then [`InvoiceStatus::Closed`] unless a payment is pending.
+    pub fn new(
+        wallet: WalletClient,
+        store: S,
+        account_index: u32,
+        confirmations: u64,
+        grace_period: ChronoDuration,
+    ) -> Self {
+        Self {
+            wallet,
+            store,
+            account_index,
+            confirmations,
+            grace_period,
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Create and store an invoice of `amount`, expiring after `expires_in`, with a new
+    /// subaddress labelled with `id`.
+    pub async fn create_invoice(
+        &self,
+        id: String,
+        amount: Amount,
+        expires_in: ChronoDuration,
+    ) -> anyhow::Result<Invoice> {
+        anyhow::ensure!(
+            self.store.get(&id).await?.is_none(),
+            "Invoice {} already exists",
+            id
+        );
+
+        let (address, minor) = self
+            .wallet
+            .create_address(self.account_index, Some(id.clone()))
+            .await?;
+        let created_height = self.wallet.get_height().await?.get();
+        let created_at = Utc::now();
+        let invoice = Invoice {
+            id,
+            address,
+            subaddr_index: subaddress::Index {
+                major: self.account_index,
+                minor,
+            },
+            amount,
+            created_at,
+            created_height,
+            expires_at: created_at + expires_in,
+            status: InvoiceStatus::Unpaid,
+            received: Amount::ZERO,
+            pending: Amount::ZERO,
+            payments: vec![],
+        };
+        self.store.save(invoice.clone()).await?;
+
+        Ok(invoice)
+    }
+
+    /// Refresh the wallet, update the open invoices with their incoming transfers, and return
+    /// the invoices whose status changed. Only the transfers mined since the creation of the
+    /// oldest open invoice are fetched.
+    pub async fn poll(&self) -> anyhow::Result<Vec<InvoiceEvent>> {
+        self.poll_with(|subaddr_indices, min_height| async move {
+            self.wallet.refresh(None).await?;
+            let category_selector = [GetTransfersCategory::In, GetTransfersCategory::Pool]
+                .iter()
+                .map(|category| (category.clone(), true))
+                .collect();
+            let transfers = self
+                .wallet
+                .get_transfers(GetTransfersSelector {
+                    category_selector,
+                    account_index: Some(self.account_index),
+                    subaddr_indices: Some(subaddr_indices),
+                    block_height_filter: Some(BlockHeightFilter {
+                        min_height: Some(min_height),
+                        max_height: None,
+                    }),
+                })
+                .await?;
+            Ok((transfers.into_values().flatten().collect(), Utc::now()))
+        })
+        .await
+    }
+
+    /// [`Self::poll`], with the incoming transfers to the given subaddresses of the account,
+    /// mined above the given height, and the time they were fetched at, returned by `fetch`.
+    pub(crate) async fn poll_with<F, Fut>(&self, fetch: F) -> anyhow::Result<Vec<InvoiceEvent>>
+    where
+        F: FnOnce(Vec<u32>, u64) -> Fut,
+        Fut: Future<Output = anyhow::Result<(Vec<GotTransfer>, DateTime<Utc>)>>,
+    {
+        let invoices = self.store.open_invoices().await?;
+        if invoices.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // the exclusive minimum height is `confirmations` blocks below the oldest invoice, so that
+        // payments mined again lower after a reorg are still found
+        let min_height = invoices
+            .iter()
+            .map(|i| i.created_height)
+            .min()
+            .unwrap_or_default()
+            .saturating_sub(self.confirmations + 1);
+        let subaddr_indices = invoices.iter().map(|i| i.subaddr_index.minor).collect();
+        let (transfers, now) = fetch(subaddr_indices, min_height).await?;
+        let mut by_subaddress: HashMap<u32, Vec<GotTransfer>> = HashMap::new();
+        for transfer in transfers {
+            by_subaddress
+                .entry(transfer.subaddr_index.minor)
+                .or_default()
+                .push(transfer);
+        }
+
+        let mut events = vec![];
+        for mut invoice in invoices {
+            let transfers = by_subaddress
+                .remove(&invoice.subaddr_index.minor)
+                .unwrap_or_default();
+            let payments = invoice.payments.clone();
+            let previous_status =
+                invoice.update(&transfers, self.confirmations, self.grace_period, now);
+
+            if previous_status.is_some() || payments != invoice.payments {
+                self.store.save(invoice.clone()).await?;
+            }
+            if let Some(previous_status) = previous_status {
+                events.push(InvoiceEvent {
+                    previous_status,
+                    invoice,
+                });
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Stream the events of [`Self::poll`], called every `poll_interval`. Errors are yielded as
+    /// they happen and polling goes on afterwards, so the stream never ends on its own.
+    pub fn into_stream(
+        self,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = anyhow::Result<InvoiceEvent>> {
+        let processor = Arc::new(self);
+
+        poll_stream(poll_interval, move || {
+            let processor = processor.clone();
+            async move { processor.poll().await }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_address;
+    use crate::{GetTransfersCategory, RpcClient};
+    use chrono::NaiveDateTime;
+    use monero::{util::address::PaymentId, Network};
+    use std::num::NonZeroU64;
+
+    fn address() -> Address {
+        test_address(1, Network::Mainnet)
+    }
+
+    fn grace_period() -> ChronoDuration {
+        ChronoDuration::seconds(50)
+    }
+
+    fn time(seconds: i64) -> DateTime<Utc> {
+        DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(seconds, 0), Utc)
+    }
+
+    fn invoice(amount: u64) -> Invoice {
+        Invoice {
+            id: "order-1".to_string(),
+            address: address(),
+            subaddr_index: subaddress::Index { major: 0, minor: 1 },
+            amount: Amount::from_pico(amount),
+            created_at: time(0),
+            created_height: 1000,
+            expires_at: time(100),
+            status: InvoiceStatus::Unpaid,
+            received: Amount::ZERO,
+            pending: Amount::ZERO,
+            payments: vec![],
+        }
+    }
+
+    fn transfer(txid: u8, amount: u64, confirmations: u64, timestamp: i64) -> GotTransfer {
+        GotTransfer {
+            address: address(),
+            amount: Amount::from_pico(amount),
+            confirmations: Some(confirmations),
+            double_spend_seen: false,
+            fee: Amount::ZERO,
+            height: match NonZeroU64::new(confirmations) {
+                Some(_) => TransferHeight::Confirmed(NonZeroU64::new(1000).unwrap()),
+                None => TransferHeight::InPool,
+            },
+            note: "".to_string(),
+            payment_id: HashString(PaymentId::zero()),
+            subaddr_index: subaddress::Index { major: 0, minor: 1 },
+            suggested_confirmations_threshold: 1,
+            timestamp: time(timestamp),
+            txid: HashString(vec![txid; 32]),
+            transfer_type: if confirmations == 0 {
+                GetTransfersCategory::Pool
+            } else {
+                GetTransfersCategory::In
+            },
+            unlock_time: 0,
+        }
+    }
+
+    #[test]
+    fn invoice_partial_then_paid() {
+        let mut invoice = invoice(100);
+
+        assert_eq!(
+            invoice.update(&[transfer(1, 40, 0, 10)], 2, grace_period(), time(10)),
+            None
+        );
+        assert_eq!(invoice.pending, Amount::from_pico(40));
+
+        assert_eq!(
+            invoice.update(&[transfer(1, 40, 2, 20)], 2, grace_period(), time(20)),
+            Some(InvoiceStatus::Unpaid)
+        );
+        assert_eq!(invoice.status, InvoiceStatus::PartiallyPaid);
+        assert_eq!(invoice.received, Amount::from_pico(40));
+        // first seen in the pool
+        assert_eq!(invoice.payments[0].first_seen, time(10));
+
+        assert_eq!(
+            invoice.update(
+                &[transfer(1, 40, 3, 20), transfer(2, 60, 1, 30)],
+                2,
+                grace_period(),
+                time(30)
+            ),
+            None
+        );
+        assert_eq!(invoice.pending, Amount::from_pico(60));
+
+        assert_eq!(
+            invoice.update(
+                &[transfer(1, 40, 4, 20), transfer(2, 60, 2, 30)],
+                2,
+                grace_period(),
+                time(40)
+            ),
+            Some(InvoiceStatus::PartiallyPaid)
+        );
+        assert_eq!(invoice.status, InvoiceStatus::Paid);
+        assert!(invoice.status.is_final());
+    }
+
+    #[test]
+    fn invoice_overpaid() {
+        let mut invoice = invoice(100);
+        // two outputs of the same transaction
+        invoice.update(
+            &[transfer(1, 70, 1, 10), transfer(1, 50, 1, 10)],
+            1,
+            grace_period(),
+            time(10),
+        );
+        assert_eq!(invoice.status, InvoiceStatus::Overpaid);
+        assert_eq!(invoice.received, Amount::from_pico(120));
+        assert_eq!(invoice.payments.len(), 1);
+    }
+
+    #[test]
+    fn invoice_expired_then_paid_late() {
+        let mut invoice = invoice(100);
+        invoice.update(&[transfer(1, 40, 1, 10)], 1, grace_period(), time(10));
+        assert_eq!(invoice.status, InvoiceStatus::PartiallyPaid);
+
+        assert_eq!(
+            invoice.update(&[transfer(1, 40, 2, 10)], 1, grace_period(), time(101)),
+            Some(InvoiceStatus::PartiallyPaid)
+        );
+        assert_eq!(invoice.status, InvoiceStatus::Expired);
+        assert!(!invoice.status.is_final());
+
+        assert_eq!(
+            invoice.update(
+                &[transfer(1, 40, 3, 10), transfer(2, 60, 1, 150)],
+                1,
+                grace_period(),
+                time(150)
+            ),
+            Some(InvoiceStatus::Expired)
+        );
+        assert_eq!(invoice.status, InvoiceStatus::PaidLate);
+    }
+
+    #[test]
+    fn invoice_expired_then_closed() {
+        let mut invoice = invoice(100);
+        invoice.update(&[transfer(1, 40, 1, 10)], 1, grace_period(), time(101));
+        assert_eq!(invoice.status, InvoiceStatus::Expired);
+
+        // a late payment is still pending at the end of the grace period
+        assert_eq!(
+            invoice.update(
+                &[transfer(1, 40, 1, 10), transfer(2, 10, 0, 140)],
+                1,
+                grace_period(),
+                time(151)
+            ),
+            None
+        );
+
+        assert_eq!(
+            invoice.update(
+                &[transfer(1, 40, 1, 10), transfer(2, 10, 1, 140)],
+                1,
+                grace_period(),
+                time(160)
+            ),
+            Some(InvoiceStatus::Expired)
+        );
+        assert_eq!(invoice.status, InvoiceStatus::Closed);
+        assert_eq!(invoice.received, Amount::from_pico(50));
+        assert!(invoice.status.is_final());
+    }
+
+    #[test]
+    fn in_memory_invoice_store() {
+        futures::executor::block_on(async {
+            let store = InMemoryInvoiceStore::default();
+            assert_eq!(store.get("order-1").await.unwrap(), None);
+
+            let mut paid = invoice(100);
+            paid.id = "order-2".to_string();
+            paid.status = InvoiceStatus::Paid;
+            store.save(invoice(100)).await.unwrap();
+            store.save(paid.clone()).await.unwrap();
+
+            assert_eq!(store.get("order-2").await.unwrap(), Some(paid));
+            assert_eq!(store.open_invoices().await.unwrap(), vec![invoice(100)]);
+        });
+    }
+
+    #[tokio::test]
+    async fn payment_processor_poll() {
+        let processor = PaymentProcessor::new(
+            RpcClient::new("http://127.0.0.1:18083".to_string()).wallet(),
+            InMemoryInvoiceStore::default(),
+            0,
+            1,
+            grace_period(),
+        );
+        let mut other = invoice(100);
+        other.id = "order-2".to_string();
+        other.subaddr_index.minor = 2;
+        other.created_height = 990;
+        processor.store().save(invoice(100)).await.unwrap();
+        processor.store().save(other).await.unwrap();
+
+        let mut paid = transfer(2, 100, 1, 10);
+        paid.subaddr_index.minor = 2;
+        let events = processor
+            .poll_with(|subaddr_indices, min_height| {
+                assert_eq!(subaddr_indices, vec![1, 2]);
+                assert_eq!(min_height, 988);
+                future::ready(Ok((vec![transfer(1, 40, 0, 10), paid], time(10))))
+            })
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].previous_status, InvoiceStatus::Unpaid);
+        assert_eq!(events[0].invoice.id, "order-2");
+        assert_eq!(events[0].invoice.status, InvoiceStatus::Paid);
+        // the pending payment is saved even though the status did not change
+        let pending = processor.store().get("order-1").await.unwrap().unwrap();
+        assert_eq!(pending.pending, Amount::from_pico(40));
+
+        let events = processor
+            .poll_with(|subaddr_indices, min_height| {
+                // only the invoices that are still open count
+                assert_eq!(subaddr_indices, vec![1]);
+                assert_eq!(min_height, 998);
+                future::ready(Ok((vec![transfer(1, 40, 1, 10)], time(120))))
+            })
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].previous_status, InvoiceStatus::Unpaid);
+        assert_eq!(events[0].invoice.status, InvoiceStatus::Expired);
+
+        let events = processor
+            .poll_with(|_, _| future::ready(Ok((vec![transfer(1, 40, 2, 10)], time(151)))))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].previous_status, InvoiceStatus::Expired);
+        assert_eq!(events[0].invoice.status, InvoiceStatus::Closed);
+
+        // no invoice is open anymore, so nothing is fetched
+        assert!(processor.store().open_invoices().await.unwrap().is_empty());
+        let events = processor
+            .poll_with(|_, _| future::ready(Err(anyhow::anyhow!("nothing to fetch"))))
+            .await
+            .unwrap();
+        assert!(events.is_empty());
+    }
+}
@@ -0,0 +1,711 @@
+// Copyright 2019-2022 Artem Vorotnikov and Monero Rust Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{TransferOptions, TransferPriority, TxMetadata, WalletClient};
+use monero::{cryptonote::hash::Hash as CryptoNoteHash, Address, Amount};
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    time::{Duration, Instant},
+};
+use tracing::*;
+
+/// Maximum number of outputs of a transaction, change included.
+pub const MAX_TX_OUTPUTS: usize = 16;
+
+/// Withdrawal sent by a [`PayoutQueue`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Withdrawal {
+    pub id: String,
+    pub address: Address,
+    pub amount: Amount,
+}
+
+/// Withdrawals sent together in one transaction by a [`PayoutQueue`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutBatch {
+    pub tx_hash: CryptoNoteHash,
+    pub fee: Amount,
+    pub withdrawals: Vec<Withdrawal>,
+}
+
+/// Argument type of [`PayoutQueue::new`].
+#[derive(Clone, Debug)]
+pub struct PayoutQueueOptions {
+    pub account_index: Option<u32>,
+    pub priority: TransferPriority,
+    /// Maximum number of withdrawals per transaction, at most `MAX_TX_OUTPUTS - 1` to leave room
+    /// for the change output.
+    pub max_destinations: usize,
+    /// How often to retry a batch while the wallet outputs are still locked.
+    pub unlock_poll_interval: Duration,
+    /// How long to wait for locked outputs before failing; `None` waits forever.
+    pub unlock_timeout: Option<Duration>,
+}
+
+impl Default for PayoutQueueOptions {
+    fn default() -> Self {
+        Self {
+            account_index: None,
+            priority: TransferPriority::Default,
+            max_destinations: MAX_TX_OUTPUTS - 1,
+            unlock_poll_interval: Duration::from_secs(30),
+            unlock_timeout: None,
+        }
+    }
+}
+
+/// Wallet-rpc error code of a transaction too large to be sent.
+const WALLET_RPC_ERROR_CODE_TX_TOO_LARGE: i64 = -18;
+/// Wallet-rpc error code of a wallet with enough money, but not unlocked yet.
+const WALLET_RPC_ERROR_CODE_NOT_ENOUGH_UNLOCKED_MONEY: i64 = -37;
+
+/// Error code of a wallet-rpc error.
+fn wallet_rpc_error_code(e: &anyhow::Error) -> Option<i64> {
+    match e.downcast_ref::<jsonrpc_core::Error>()?.code {
+        jsonrpc_core::ErrorCode::ServerError(code) => Some(code),
+        _ => None,
+    }
+}
+
+/// Whether the wallet refused a transaction for being too large.
+fn is_tx_too_large(e: &anyhow::Error) -> bool {
+    wallet_rpc_error_code(e) == Some(WALLET_RPC_ERROR_CODE_TX_TOO_LARGE)
+}
+
+/// Whether the wallet has enough money, but not unlocked yet.
+fn is_locked(e: &anyhow::Error) -> bool {
+    wallet_rpc_error_code(e) == Some(WALLET_RPC_ERROR_CODE_NOT_ENOUGH_UNLOCKED_MONEY)
+}
+
+/// Batch of a [`PayoutQueue`] whose transaction was built, but not known to be relayed: its relay
+/// failed, or was dropped before returning. It is not sent again by [`PayoutQueue::process`],
+/// see [`PayoutQueue::needs_reconciliation`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnrelayedBatch {
+    pub batch: PayoutBatch,
+    /// Metadata to relay the transaction with wallet `relay_tx`.
+    pub tx_metadata: TxMetadata,
+}
+
+/// What [`send_batches`] managed to send.
+#[derive(Debug)]
+pub(crate) struct SendOutcome {
+    pub(crate) sent: Vec<PayoutBatch>,
+    pub(crate) error: Option<anyhow::Error>,
+}
+
+/// Send the `pending` withdrawals in batches of at most `max_destinations`: each transaction is
+/// built with `build`, without relaying it, then relayed with `relay`. Batches that are too large
+/// are split in two and built again, and batches failing on locked outputs are built again every
+/// `unlock_poll_interval`, until `unlock_timeout`. Any other error stops sending.
+///
+/// Once built, the withdrawals of a batch leave `pending` for `unrelayed`, before the relay, and
+/// only leave `unrelayed`, with their transaction hash in `tx_hashes`, once `relay` succeeds. If
+/// the relay fails or the future is dropped, the batch stays in `unrelayed`, and is never built
+/// again, so that a transaction relayed despite a client side error is not paid twice.
+pub(crate) async fn send_batches<B, BFut, R, RFut>(
+    pending: &mut VecDeque<Withdrawal>,
+    unrelayed: &mut Vec<UnrelayedBatch>,
+    tx_hashes: &mut HashMap<String, CryptoNoteHash>,
+    options: &PayoutQueueOptions,
+    mut build: B,
+    mut relay: R,
+) -> SendOutcome
+where
+    B: FnMut(Vec<Withdrawal>) -> BFut,
+    BFut: Future<Output = anyhow::Result<(CryptoNoteHash, Amount, TxMetadata)>>,
+    R: FnMut(TxMetadata) -> RFut,
+    RFut: Future<Output = anyhow::Result<()>>,
+{
+    let max_destinations = options.max_destinations.clamp(1, MAX_TX_OUTPUTS - 1);
+    // batches are consecutive withdrawals at the front of `pending`
+    let mut batch_sizes: VecDeque<usize> = (0..pending.len())
+        .step_by(max_destinations)
+        .map(|start| max_destinations.min(pending.len() - start))
+        .collect();
+    let mut sent = vec![];
+    let mut locked_since: Option<Instant> = None;
+
+    while let Some(size) = batch_sizes.pop_front() {
+        let batch: Vec<_> = pending.iter().take(size).cloned().collect();
+        let e = match build(batch.clone()).await {
+            Ok((tx_hash, fee, tx_metadata)) => {
+                locked_since = None;
+                pending.drain(..size);
+                let batch = PayoutBatch {
+                    tx_hash,
+                    fee,
+                    withdrawals: batch,
+                };
+                unrelayed.push(UnrelayedBatch {
+                    batch: batch.clone(),
+                    tx_metadata: tx_metadata.clone(),
+                });
+
+                if let Err(e) = relay(tx_metadata).await {
+                    let e = e.context(format!(
+                        "Failed to relay transaction {:x}, which needs reconciliation",
+                        tx_hash
+                    ));
+                    return SendOutcome {
+                        sent,
+                        error: Some(e),
+                    };
+                }
+                unrelayed.retain(|unrelayed| unrelayed.batch.tx_hash != tx_hash);
+                for withdrawal in &batch.withdrawals {
+                    tx_hashes.insert(withdrawal.id.clone(), tx_hash);
+                }
+                sent.push(batch);
+                continue;
+            }
+            Err(e) => e,
+        };
+
+        if is_tx_too_large(&e) && size > 1 {
+            debug!("Splitting a batch of {} withdrawals: {}", size, e);
+            batch_sizes.push_front(size - size / 2);
+            batch_sizes.push_front(size / 2);
+            continue;
+        }
+
+        let error = if is_locked(&e) {
+            let since = *locked_since.get_or_insert_with(Instant::now);
+            match options.unlock_timeout {
+                Some(timeout) if since.elapsed() >= timeout => {
+                    Some(e.context(format!("Outputs still locked after {:?}", timeout)))
+                }
+                _ => {
+                    debug!("Waiting for outputs to unlock: {}", e);
+                    tokio::time::sleep(options.unlock_poll_interval).await;
+                    None
+                }
+            }
+        } else {
+            Some(e)
+        };
+
+        batch_sizes.push_front(size);
+        if error.is_some() {
+            return SendOutcome { sent, error };
+        }
+    }
+
+    SendOutcome { sent, error: None }
+}
+
+/// Queue of withdrawals, sent as multi-destination transfers by [`PayoutQueue::process`].
+#[derive(Debug)]
+pub struct PayoutQueue {
+    wallet: WalletClient,
+    options: PayoutQueueOptions,
+    pending: VecDeque<Withdrawal>,
+    unrelayed: Vec<UnrelayedBatch>,
+    tx_hashes: HashMap<String, CryptoNoteHash>,
+}
+
+impl PayoutQueue {
+    pub fn new(wallet: WalletClient, options: PayoutQueueOptions) -> Self {
+        Self {
+            wallet,
+            options,
+            pending: VecDeque::new(),
+            unrelayed: vec![],
+            tx_hashes: HashMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, withdrawal: Withdrawal) {
+        self.pending.push_back(withdrawal);
+    }
+
+    /// Withdrawals not sent yet.
+    pub fn pending(&self) -> impl Iterator<Item = &Withdrawal> {
+        self.pending.iter()
+    }
+
+    /// Batches whose transaction was built but may not have been relayed, e.g. because the relay
+    /// timed out. Check whether the daemon knows their transaction, then call
+    /// [`Self::mark_relayed`] or [`Self::requeue`].
+    pub fn needs_reconciliation(&self) -> impl Iterator<Item = &UnrelayedBatch> {
+        self.unrelayed.iter()
+    }
+
+    /// Record the batch of `tx_hash`, returned by [`Self::needs_reconciliation`], as relayed.
+    /// Returns `false` if there is no such batch.
+    pub fn mark_relayed(&mut self, tx_hash: CryptoNoteHash) -> bool {
+        match self.take_unrelayed(tx_hash) {
+            Some(unrelayed) => {
+                for withdrawal in unrelayed.batch.withdrawals {
+                    self.tx_hashes.insert(withdrawal.id, tx_hash);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Put the withdrawals of the batch of `tx_hash`, returned by [`Self::needs_reconciliation`],
+    /// back at the front of the queue, to be sent again. Only call it once sure that the
+    /// transaction was not relayed, and will not be. Returns `false` if there is no such batch.
+    pub fn requeue(&mut self, tx_hash: CryptoNoteHash) -> bool {
+        match self.take_unrelayed(tx_hash) {
+            Some(unrelayed) => {
+                for withdrawal in unrelayed.batch.withdrawals.into_iter().rev() {
+                    self.pending.push_front(withdrawal);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn take_unrelayed(&mut self, tx_hash: CryptoNoteHash) -> Option<UnrelayedBatch> {
+        let index = self
+            .unrelayed
+            .iter()
+            .position(|unrelayed| unrelayed.batch.tx_hash == tx_hash)?;
+        Some(self.unrelayed.remove(index))
+    }
+
+    /// Hash of the transaction a withdrawal was sent in.
+    pub fn tx_hash(&self, withdrawal_id: &str) -> Option<CryptoNoteHash> {
+        self.tx_hashes.get(withdrawal_id).copied()
+    }
+
+    /// Send the pending withdrawals and return the batches sent.
+    ///
+    /// Each transaction is built without being relayed, then relayed with `relay_tx`, and
+    /// withdrawals stay in the queue until their transaction is built, so that the future can be
+    /// dropped, e.g. on a timeout, without losing any of them. A batch whose relay fails, or is
+    /// dropped, may still have reached the daemon, so it is moved to
+    /// [`Self::needs_reconciliation`] instead of being sent again. On error, or when the future is
+    /// dropped, the batches sent before are only available through [`Self::tx_hash`].
+    pub async fn process(&mut self) -> anyhow::Result<Vec<PayoutBatch>> {
+        let wallet = self.wallet.clone();
+        let options = self.options.clone();
+        let relay_wallet = self.wallet.clone();
+
+        self.send_with(
+            |batch| {
+                let wallet = wallet.clone();
+                let options = options.clone();
+                async move {
+                    let destinations = batch
+                        .into_iter()
+                        .map(|withdrawal| (withdrawal.address, withdrawal.amount))
+                        .collect();
+                    let transfer_options = TransferOptions {
+                        account_index: options.account_index,
+                        do_not_relay: Some(true),
+                        get_tx_metadata: Some(true),
+                        ..Default::default()
+                    };
+
+                    let data = wallet
+                        .transfer(destinations, options.priority, transfer_options)
+                        .await?;
+                    Ok((data.tx_hash.0, data.fee, data.tx_metadata.0))
+                }
+            },
+            |tx_metadata| {
+                let wallet = relay_wallet.clone();
+                async move {
+                    wallet.relay_tx(tx_metadata).await?;
+                    Ok(())
+                }
+            },
+        )
+        .await
+    }
+
+    /// Same as [`Self::process`], with the transactions built by `build` and relayed by `relay`.
+    pub(crate) async fn send_with<B, BFut, R, RFut>(
+        &mut self,
+        build: B,
+        relay: R,
+    ) -> anyhow::Result<Vec<PayoutBatch>>
+    where
+        B: FnMut(Vec<Withdrawal>) -> BFut,
+        BFut: Future<Output = anyhow::Result<(CryptoNoteHash, Amount, TxMetadata)>>,
+        R: FnMut(TxMetadata) -> RFut,
+        RFut: Future<Output = anyhow::Result<()>>,
+    {
+        let outcome = send_batches(
+            &mut self.pending,
+            &mut self.unrelayed,
+            &mut self.tx_hashes,
+            &self.options,
+            build,
+            relay,
+        )
+        .await;
+
+        match outcome.error {
+            Some(e) => Err(e),
+            None => Ok(outcome.sent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_address;
+    use crate::RpcClient;
+    use futures::FutureExt;
+    use monero::Network;
+    use std::sync::Mutex;
+
+    fn withdrawals(count: u8) -> Vec<Withdrawal> {
+        (1..=count)
+            .map(|i| Withdrawal {
+                id: i.to_string(),
+                address: test_address(i, Network::Mainnet),
+                amount: Amount::from_pico(u64::from(i)),
+            })
+            .collect()
+    }
+
+    fn rpc_error(code: i64, message: &str) -> anyhow::Error {
+        jsonrpc_core::Error {
+            code: jsonrpc_core::ErrorCode::ServerError(code),
+            message: message.to_string(),
+            data: None,
+        }
+        .into()
+    }
+
+    fn options(max_destinations: usize, unlock_timeout: Option<Duration>) -> PayoutQueueOptions {
+        PayoutQueueOptions {
+            max_destinations,
+            unlock_poll_interval: Duration::from_millis(1),
+            unlock_timeout,
+            ..Default::default()
+        }
+    }
+
+    fn built(batch: &[Withdrawal]) -> (CryptoNoteHash, Amount, TxMetadata) {
+        (
+            CryptoNoteHash::new(&batch[0].id),
+            Amount::from_pico(1),
+            TxMetadata(batch[0].id.as_bytes().to_vec()),
+        )
+    }
+
+    /// Build with the scripted errors, then succeed, and relay with the scripted errors; return
+    /// the sizes of the attempted batches, the unsent and the unrelayed withdrawals.
+    async fn send_scripted(
+        withdrawals: Vec<Withdrawal>,
+        options: PayoutQueueOptions,
+        build_errors: Vec<anyhow::Error>,
+        relay_errors: Vec<Option<anyhow::Error>>,
+    ) -> (
+        SendOutcome,
+        Vec<usize>,
+        Vec<Withdrawal>,
+        Vec<UnrelayedBatch>,
+    ) {
+        let build_errors = Mutex::new(VecDeque::from(build_errors));
+        let relay_errors = Mutex::new(VecDeque::from(relay_errors));
+        let attempts = Mutex::new(vec![]);
+        let mut pending = VecDeque::from(withdrawals);
+        let mut unrelayed = vec![];
+
+        let outcome = send_batches(
+            &mut pending,
+            &mut unrelayed,
+            &mut HashMap::new(),
+            &options,
+            |batch| {
+                attempts.lock().unwrap().push(batch.len());
+                let res = match build_errors.lock().unwrap().pop_front() {
+                    Some(e) => Err(e),
+                    None => Ok(built(&batch)),
+                };
+                async move { res }
+            },
+            |_| {
+                let res = match relay_errors.lock().unwrap().pop_front().flatten() {
+                    Some(e) => Err(e),
+                    None => Ok(()),
+                };
+                async move { res }
+            },
+        )
+        .await;
+
+        (
+            outcome,
+            attempts.into_inner().unwrap(),
+            pending.into_iter().collect(),
+            unrelayed,
+        )
+    }
+
+    #[tokio::test]
+    async fn send_batches_respects_max_destinations() {
+        let (outcome, attempts, unsent, unrelayed) =
+            send_scripted(withdrawals(40), options(100, None), vec![], vec![]).await;
+        assert_eq!(attempts, vec![15, 15, 10]);
+        assert!(outcome.error.is_none());
+        assert!(unsent.is_empty());
+        assert!(unrelayed.is_empty());
+        assert_eq!(
+            outcome
+                .sent
+                .iter()
+                .flat_map(|b| b.withdrawals.clone())
+                .collect::<Vec<_>>(),
+            withdrawals(40)
+        );
+        assert_eq!(outcome.sent[1].tx_hash, CryptoNoteHash::new("16"));
+    }
+
+    #[tokio::test]
+    async fn send_batches_splits_too_large_batches() {
+        let too_big = || rpc_error(WALLET_RPC_ERROR_CODE_TX_TOO_LARGE, "transaction too big");
+        let (outcome, attempts, _, _) = send_scripted(
+            withdrawals(8),
+            options(8, None),
+            vec![too_big(), too_big()],
+            vec![],
+        )
+        .await;
+        assert_eq!(attempts, vec![8, 4, 2, 2, 4]);
+        assert_eq!(
+            outcome
+                .sent
+                .iter()
+                .map(|b| b.withdrawals.len())
+                .collect::<Vec<_>>(),
+            vec![2, 2, 4]
+        );
+        assert!(outcome.error.is_none());
+
+        // a single withdrawal cannot be split
+        let (outcome, attempts, unsent, _) =
+            send_scripted(withdrawals(3), options(1, None), vec![too_big()], vec![]).await;
+        assert_eq!(attempts, vec![1]);
+        assert!(outcome.sent.is_empty());
+        assert_eq!(unsent, withdrawals(3));
+        assert_eq!(
+            outcome.error.unwrap().to_string(),
+            "Server error: transaction too big"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_batches_waits_for_locked_outputs() {
+        let locked = || {
+            rpc_error(
+                WALLET_RPC_ERROR_CODE_NOT_ENOUGH_UNLOCKED_MONEY,
+                "not enough unlocked money",
+            )
+        };
+        let (outcome, attempts, _, _) = send_scripted(
+            withdrawals(2),
+            options(1, None),
+            vec![locked(), locked()],
+            vec![],
+        )
+        .await;
+        assert_eq!(attempts, vec![1, 1, 1, 1]);
+        assert_eq!(outcome.sent.len(), 2);
+        assert!(outcome.error.is_none());
+
+        let (outcome, _, unsent, _) = send_scripted(
+            withdrawals(2),
+            options(1, Some(Duration::from_millis(0))),
+            vec![locked(), locked()],
+            vec![],
+        )
+        .await;
+        assert!(outcome.sent.is_empty());
+        assert_eq!(unsent, withdrawals(2));
+        assert_eq!(
+            outcome.error.unwrap().to_string(),
+            "Outputs still locked after 0ns"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_batches_stops_on_other_errors() {
+        let (outcome, attempts, unsent, _) = send_scripted(
+            withdrawals(3),
+            options(1, None),
+            vec![rpc_error(-17, "not enough money")],
+            vec![],
+        )
+        .await;
+        assert_eq!(attempts, vec![1]);
+        assert!(outcome.sent.is_empty());
+        assert_eq!(unsent, withdrawals(3));
+
+        // only the wallet-rpc error code counts, not the message
+        let (outcome, attempts, unsent, _) = send_scripted(
+            withdrawals(3),
+            options(3, None),
+            vec![anyhow::Error::msg("transaction too big")],
+            vec![],
+        )
+        .await;
+        assert_eq!(attempts, vec![3]);
+        assert!(outcome.error.is_some());
+        assert_eq!(unsent, withdrawals(3));
+    }
+
+    #[tokio::test]
+    async fn send_batches_keeps_failed_relays_for_reconciliation() {
+        let (outcome, attempts, unsent, unrelayed) = send_scripted(
+            withdrawals(5),
+            options(2, None),
+            vec![],
+            vec![None, Some(anyhow::Error::msg("operation timed out"))],
+        )
+        .await;
+        assert_eq!(attempts, vec![2, 2]);
+        assert_eq!(outcome.sent.len(), 1);
+        assert_eq!(
+            outcome.error.unwrap().to_string(),
+            format!(
+                "Failed to relay transaction {:x}, which needs reconciliation",
+                CryptoNoteHash::new("3")
+            )
+        );
+        assert_eq!(unsent, withdrawals(5)[4..].to_vec());
+        assert_eq!(
+            unrelayed,
+            vec![UnrelayedBatch {
+                batch: PayoutBatch {
+                    tx_hash: CryptoNoteHash::new("3"),
+                    fee: Amount::from_pico(1),
+                    withdrawals: withdrawals(5)[2..4].to_vec(),
+                },
+                tx_metadata: TxMetadata(b"3".to_vec()),
+            }]
+        );
+    }
+
+    fn queue(withdrawals: Vec<Withdrawal>) -> PayoutQueue {
+        let mut queue = PayoutQueue::new(
+            RpcClient::new("http://127.0.0.1:18083".to_string()).wallet(),
+            options(2, None),
+        );
+        for withdrawal in withdrawals {
+            queue.push(withdrawal);
+        }
+        queue
+    }
+
+    #[test]
+    fn payout_queue_survives_cancellation() {
+        let mut queue = queue(withdrawals(5));
+
+        // the first batch is sent, and the second one is never built
+        let calls = Mutex::new(0);
+        let res = queue
+            .send_with(
+                |batch| {
+                    let mut calls = calls.lock().unwrap();
+                    *calls += 1;
+                    let first = *calls == 1;
+                    async move {
+                        if first {
+                            Ok(built(&batch))
+                        } else {
+                            futures::future::pending().await
+                        }
+                    }
+                },
+                |_| futures::future::ready(Ok(())),
+            )
+            .now_or_never();
+        assert!(res.is_none());
+
+        assert_eq!(
+            queue.pending().cloned().collect::<Vec<_>>(),
+            withdrawals(5)[2..].to_vec()
+        );
+        assert_eq!(queue.needs_reconciliation().count(), 0);
+        assert_eq!(queue.tx_hash("1"), Some(CryptoNoteHash::new("1")));
+        assert_eq!(queue.tx_hash("2"), Some(CryptoNoteHash::new("1")));
+        assert_eq!(queue.tx_hash("3"), None);
+
+        // the next batch is built, and its relay never returns
+        let res = queue
+            .send_with(
+                |batch| futures::future::ready(Ok(built(&batch))),
+                |_| futures::future::pending(),
+            )
+            .now_or_never();
+        assert!(res.is_none());
+        assert_eq!(
+            queue.pending().cloned().collect::<Vec<_>>(),
+            withdrawals(5)[4..].to_vec()
+        );
+        assert_eq!(
+            queue
+                .needs_reconciliation()
+                .map(|unrelayed| unrelayed.batch.withdrawals.clone())
+                .collect::<Vec<_>>(),
+            vec![withdrawals(5)[2..4].to_vec()]
+        );
+    }
+
+    #[test]
+    fn payout_queue_reconciliation() {
+        let mut queue = queue(withdrawals(4));
+        let res = queue
+            .send_with(
+                |batch| futures::future::ready(Ok(built(&batch))),
+                |_| futures::future::ready(Err(anyhow::Error::msg("connection reset"))),
+            )
+            .now_or_never()
+            .unwrap();
+        assert!(res.is_err());
+        assert_eq!(queue.needs_reconciliation().count(), 1);
+
+        // the unrelayed batch is not sent again
+        let res = queue
+            .send_with(
+                |batch| futures::future::ready(Ok(built(&batch))),
+                |_| futures::future::ready(Err(anyhow::Error::msg("connection reset"))),
+            )
+            .now_or_never()
+            .unwrap();
+        assert!(res.is_err());
+        assert_eq!(queue.pending().count(), 0);
+        assert_eq!(
+            queue
+                .needs_reconciliation()
+                .map(|unrelayed| unrelayed.batch.tx_hash)
+                .collect::<Vec<_>>(),
+            vec![CryptoNoteHash::new("1"), CryptoNoteHash::new("3")]
+        );
+
+        // the first one reached the daemon, and the second one did not
+        assert!(queue.mark_relayed(CryptoNoteHash::new("1")));
+        assert!(queue.requeue(CryptoNoteHash::new("3")));
+        assert!(!queue.requeue(CryptoNoteHash::new("3")));
+        assert_eq!(queue.needs_reconciliation().count(), 0);
+        assert_eq!(queue.tx_hash("2"), Some(CryptoNoteHash::new("1")));
+        assert_eq!(queue.tx_hash("3"), None);
+        assert_eq!(
+            queue.pending().cloned().collect::<Vec<_>>(),
+            withdrawals(4)[2..].to_vec()
+        );
+    }
+}
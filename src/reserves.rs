@@ -0,0 +1,166 @@
+// Copyright 2019-2022 Artem Vorotnikov and Monero Rust Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{ReserveProofCheck, WalletClient};
+use monero::{Address, Amount};
+use std::iter::once;
+
+/// Reserve proof made with [`WalletClient::get_reserve_proof`] by the wallet of `address`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReserveProof {
+    pub address: Address,
+    pub message: Option<String>,
+    pub signature: String,
+}
+
+/// Result of the check of a [`ReserveProof`] in a [`ReserveAudit`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReserveProofStatus {
+    /// The signature is valid, and proves `total - spent`.
+    Valid { total: Amount, spent: Amount },
+    /// The signature is not valid.
+    Invalid,
+    /// The address already has a valid proof earlier in the batch, so this one is not counted.
+    Duplicate,
+    /// The wallet refused the proof, e.g. for a malformed signature or an address of another
+    /// network.
+    Error(String),
+}
+
+/// Report of [`WalletClient::audit_reserve_proofs`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReserveAudit {
+    /// Every proof with its status, in the order of the batch.
+    pub proofs: Vec<(ReserveProof, ReserveProofStatus)>,
+    /// Sum of the unspent amounts proven by the valid proofs.
+    pub reserves: Amount,
+}
+
+impl ReserveAudit {
+    pub(crate) fn new(proofs: Vec<(ReserveProof, ReserveProofStatus)>) -> Self {
+        let reserves = proofs
+            .iter()
+            .fold(Amount::ZERO, |reserves, (_, status)| match status {
+                ReserveProofStatus::Valid { total, spent } if total > spent => {
+                    reserves + (*total - *spent)
+                }
+                _ => reserves,
+            });
+
+        Self { proofs, reserves }
+    }
+
+    /// Whether all the proofs of the batch are valid.
+    pub fn is_valid(&self) -> bool {
+        self.proofs
+            .iter()
+            .all(|(_, status)| matches!(status, ReserveProofStatus::Valid { .. }))
+    }
+}
+
+impl WalletClient {
+    /// Check a batch of reserve proofs, e.g. one per wallet of an exchange, and sum the reserves
+    /// they prove. Any wallet can be opened to check them.
+    ///
+    /// An address is only counted once, for its first valid proof, and proofs refused by the
+    /// wallet are reported as [`ReserveProofStatus::Error`]. Other errors, such as connection
+    /// errors, fail the audit. Note that different addresses of the same wallet, i.e.
+    /// subaddresses, prove the same outputs and cannot be told apart.
+    pub async fn audit_reserve_proofs(
+        &self,
+        proofs: Vec<ReserveProof>,
+    ) -> anyhow::Result<ReserveAudit> {
+        let mut checked = Vec::with_capacity(proofs.len());
+        for proof in proofs {
+            if checked.iter().any(|(p, status): &(ReserveProof, _)| {
+                p.address == proof.address && matches!(status, ReserveProofStatus::Valid { .. })
+            }) {
+                checked.push((proof, ReserveProofStatus::Duplicate));
+                continue;
+            }
+
+            // like the proofs refused by the wallet, when bound to another network
+            if let Err(e) = self.inner.check_addresses(once(&proof.address)) {
+                checked.push((proof, ReserveProofStatus::Error(e.to_string())));
+                continue;
+            }
+
+            let status = match self
+                .check_reserve_proof(
+                    proof.address,
+                    proof.message.clone(),
+                    proof.signature.clone(),
+                )
+                .await
+            {
+                Ok(ReserveProofCheck {
+                    good: true,
+                    total,
+                    spent,
+                }) => ReserveProofStatus::Valid { total, spent },
+                Ok(_) => ReserveProofStatus::Invalid,
+                Err(e) => match e.downcast_ref::<jsonrpc_core::Error>() {
+                    Some(e) => ReserveProofStatus::Error(e.message.clone()),
+                    None => return Err(e),
+                },
+            };
+            checked.push((proof, status));
+        }
+
+        Ok(ReserveAudit::new(checked))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_address;
+    use monero::Network;
+
+    fn proof(i: u8) -> ReserveProof {
+        ReserveProof {
+            address: test_address(i, Network::Mainnet),
+            message: None,
+            signature: format!("ReserveProofV2{}", i),
+        }
+    }
+
+    #[test]
+    fn reserve_audit_sums_valid_proofs() {
+        let valid = |total, spent| ReserveProofStatus::Valid {
+            total: Amount::from_pico(total),
+            spent: Amount::from_pico(spent),
+        };
+
+        let audit = ReserveAudit::new(vec![
+            (proof(1), valid(100, 30)),
+            (proof(2), valid(50, 0)),
+            (proof(3), valid(20, 20)),
+        ]);
+        assert_eq!(audit.reserves, Amount::from_pico(120));
+        assert!(audit.is_valid());
+
+        let audit = ReserveAudit::new(vec![
+            (proof(1), valid(100, 30)),
+            (proof(1), ReserveProofStatus::Duplicate),
+            (proof(2), ReserveProofStatus::Invalid),
+            (
+                proof(3),
+                ReserveProofStatus::Error("Invalid signature".to_string()),
+            ),
+        ]);
+        assert_eq!(audit.reserves, Amount::from_pico(70));
+        assert!(!audit.is_valid());
+    }
+}
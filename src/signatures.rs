@@ -0,0 +1,225 @@
+// Copyright 2019-2022 Artem Vorotnikov and Monero Rust Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{SignatureCheck, SignatureType};
+use monero::{
+    consensus::encode::serialize, cryptonote::hash::Hash as CryptoNoteHash, Address, PrivateKey,
+    PublicKey, VarInt,
+};
+
+/// Domain separator of `SigV2` message signatures, with its trailing NUL.
+const HASH_KEY_MESSAGE_SIGNING: &[u8] = b"MoneroMessageSignature\0";
+
+/// Compressed identity point.
+const IDENTITY: [u8; 32] = {
+    let mut identity = [0; 32];
+    identity[0] = 1;
+    identity
+};
+
+/// Hash signed by a `SigV2` signature, which commits to both keys of the address and to the key
+/// used to sign.
+fn message_hash(data: &[u8], address: &Address, signature_type: SignatureType) -> CryptoNoteHash {
+    let mode = match signature_type {
+        SignatureType::Spend => 0,
+        SignatureType::View => 1,
+    };
+
+    let mut buf = HASH_KEY_MESSAGE_SIGNING.to_vec();
+    buf.extend_from_slice(address.public_spend.as_bytes());
+    buf.extend_from_slice(address.public_view.as_bytes());
+    buf.push(mode);
+    buf.extend(serialize(&VarInt(data.len() as u64)));
+    buf.extend_from_slice(data);
+
+    CryptoNoteHash::new(buf)
+}
+
+/// Check the Schnorr signature `(c, r)` of `hash` by `key`, like `crypto::check_signature` of
+/// Monero: `c` must equal `H(hash || key || r*G + c*key)`.
+fn check_signature(hash: &CryptoNoteHash, key: &PublicKey, signature: &[u8]) -> bool {
+    let (c, r) = match (
+        PrivateKey::from_slice(&signature[..32]),
+        PrivateKey::from_slice(&signature[32..]),
+    ) {
+        (Ok(c), Ok(r)) => (c, r),
+        _ => return false,
+    };
+    if c.as_bytes() == [0; 32] {
+        return false;
+    }
+
+    let commitment = PublicKey::from_private_key(&r) + c * key;
+    if commitment.as_bytes() == IDENTITY {
+        return false;
+    }
+
+    let mut buf = hash.as_bytes().to_vec();
+    buf.extend_from_slice(key.as_bytes());
+    buf.extend_from_slice(commitment.as_bytes());
+
+    CryptoNoteHash::hash_to_scalar(buf) == c
+}
+
+/// Verify a signature of `data` made by `address` with wallet `sign`, without a wallet-rpc.
+///
+/// This gives the same result as [`WalletClient::verify`](crate::WalletClient::verify), for the
+/// current `SigV2` signatures and for the older `SigV1` ones, made with the spend or the view key.
+pub fn verify_signature(data: &str, address: &Address, signature: &str) -> SignatureCheck {
+    let invalid = SignatureCheck {
+        good: false,
+        version: 0,
+        old: false,
+        signature_type: None,
+    };
+
+    let (version, encoded) = if let Some(encoded) = signature.strip_prefix("SigV1") {
+        (1, encoded)
+    } else if let Some(encoded) = signature.strip_prefix("SigV2") {
+        (2, encoded)
+    } else {
+        return invalid;
+    };
+    let signature = match base58_monero::decode(encoded) {
+        Ok(signature) if signature.len() == 64 => signature,
+        _ => return invalid,
+    };
+
+    // like the wallet, both keys are tried for `SigV1` signatures too
+    for (signature_type, key) in [
+        (SignatureType::Spend, &address.public_spend),
+        (SignatureType::View, &address.public_view),
+    ]
+    .iter()
+    {
+        let hash = if version == 1 {
+            CryptoNoteHash::new(data)
+        } else {
+            message_hash(data.as_bytes(), address, *signature_type)
+        };
+        if check_signature(&hash, key, &signature) {
+            return SignatureCheck {
+                good: true,
+                version,
+                old: version == 1,
+                signature_type: Some(*signature_type),
+            };
+        }
+    }
+
+    invalid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_key_pair;
+    use monero::{KeyPair, Network};
+
+    /// Sign like wallet `sign`, with a fixed nonce.
+    fn sign(hash: &CryptoNoteHash, secret: &PrivateKey) -> Vec<u8> {
+        let k = CryptoNoteHash::hash_to_scalar(b"nonce");
+        let key = PublicKey::from_private_key(secret);
+        let commitment = PublicKey::from_private_key(&k);
+
+        let mut buf = hash.as_bytes().to_vec();
+        buf.extend_from_slice(key.as_bytes());
+        buf.extend_from_slice(commitment.as_bytes());
+        let c = CryptoNoteHash::hash_to_scalar(buf);
+        let r = PrivateKey::from_scalar(k.scalar - c.scalar * secret.scalar);
+
+        [c.to_bytes(), r.to_bytes()].concat()
+    }
+
+    #[test]
+    fn verify_signature_spend_and_view() {
+        let key_pair = test_key_pair(1);
+        let address = Address::from_keypair(Network::Mainnet, &key_pair);
+        let data = "login nonce 1234";
+
+        let spend = format!(
+            "SigV2{}",
+            base58_monero::encode(&sign(
+                &message_hash(data.as_bytes(), &address, SignatureType::Spend),
+                &key_pair.spend
+            ))
+            .unwrap()
+        );
+        assert_eq!(
+            verify_signature(data, &address, &spend),
+            SignatureCheck {
+                good: true,
+                version: 2,
+                old: false,
+                signature_type: Some(SignatureType::Spend),
+            }
+        );
+
+        let view = format!(
+            "SigV2{}",
+            base58_monero::encode(&sign(
+                &message_hash(data.as_bytes(), &address, SignatureType::View),
+                &key_pair.view
+            ))
+            .unwrap()
+        );
+        assert_eq!(
+            verify_signature(data, &address, &view).signature_type,
+            Some(SignatureType::View)
+        );
+
+        let v1 = format!(
+            "SigV1{}",
+            base58_monero::encode(&sign(&CryptoNoteHash::new(data), &key_pair.spend)).unwrap()
+        );
+        assert_eq!(
+            verify_signature(data, &address, &v1),
+            SignatureCheck {
+                good: true,
+                version: 1,
+                old: true,
+                signature_type: Some(SignatureType::Spend),
+            }
+        );
+    }
+
+    #[test]
+    fn verify_signature_invalid() {
+        let key_pair = test_key_pair(1);
+        let address = Address::from_keypair(Network::Mainnet, &key_pair);
+        let data = "login nonce 1234";
+        let signature = format!(
+            "SigV2{}",
+            base58_monero::encode(&sign(
+                &message_hash(data.as_bytes(), &address, SignatureType::Spend),
+                &key_pair.spend
+            ))
+            .unwrap()
+        );
+
+        // another message, another address, and malformed signatures
+        assert!(!verify_signature("login nonce 1235", &address, &signature).good);
+        let other_address = Address::from_keypair(
+            Network::Mainnet,
+            &KeyPair {
+                view: key_pair.spend,
+                spend: key_pair.view,
+            },
+        );
+        assert!(!verify_signature(data, &other_address, &signature).good);
+        assert!(!verify_signature(data, &address, &signature[..40]).good);
+        assert!(!verify_signature(data, &address, &signature.replace("SigV2", "SigV3")).good);
+        assert!(!verify_signature(data, &address, "").good);
+    }
+}
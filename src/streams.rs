@@ -0,0 +1,1147 @@
+// Copyright 2019-2022 Artem Vorotnikov and Monero Rust Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    BlockHash, BlockHeaderResponse, BlockHeightFilter, DaemonJsonRpcClient, DaemonRpcClient,
+    GetBlockHeaderSelector, GetTransfersCategory, GetTransfersSelector, GotTransfer, Transaction,
+    TransferHeight, WalletClient, RESTRICTED_BLOCK_HEADERS_RANGE_LIMIT,
+    RESTRICTED_GET_TRANSACTIONS_LIMIT,
+};
+use futures::{
+    future::{BoxFuture, FutureExt},
+    lock::Mutex,
+    stream, Future, Stream,
+};
+use monero::cryptonote::hash::Hash as CryptoNoteHash;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    convert::TryFrom,
+    ops::RangeInclusive,
+    sync::Arc,
+    time::Duration,
+};
+use tracing::*;
+
+/// Maximum number of blocks [`DaemonJsonRpcClient::block_stream`] can rewind on a reorg.
+pub const MAX_REORG_DEPTH: usize = 100;
+
+/// Stream the events returned by `poll`, called right away, then every `poll_interval`. A poll
+/// that returned events is followed by another one without waiting, so that a backlog, such as
+/// blocks to catch up, is drained. Errors are yielded as they happen and polling goes on
+/// afterwards, so the stream never ends on its own.
+pub(crate) fn poll_stream<E, F, Fut>(
+    poll_interval: Duration,
+    poll: F,
+) -> impl Stream<Item = anyhow::Result<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<Vec<E>>>,
+{
+    let state = (poll, VecDeque::new(), false);
+
+    stream::unfold(state, move |(mut poll, mut pending, mut wait)| async move {
+        loop {
+            if let Some(event) = pending.pop_front() {
+                return Some((Ok(event), (poll, pending, wait)));
+            }
+
+            if wait {
+                tokio::time::sleep(poll_interval).await;
+            }
+            match poll().await {
+                Ok(events) => {
+                    wait = events.is_empty();
+                    pending.extend(events);
+                }
+                Err(e) => return Some((Err(e), (poll, pending, true))),
+            }
+        }
+    })
+}
+
+/// Item type of [`DaemonJsonRpcClient::block_stream`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BlockEvent {
+    /// A new block on top of the chain.
+    Block(BlockHeaderResponse),
+    /// The daemon switched to another chain. Blocks above `fork_height` are `orphaned` (in
+    /// ascending order), and the blocks of the new chain follow as [`BlockEvent::Block`].
+    Reorg {
+        fork_height: u64,
+        orphaned: Vec<BlockHeaderResponse>,
+    },
+}
+
+/// Last blocks yielded by the block stream, used to detect and rewind reorgs.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ChainTracker {
+    headers: VecDeque<BlockHeaderResponse>,
+}
+
+impl ChainTracker {
+    pub(crate) fn tip(&self) -> Option<&BlockHeaderResponse> {
+        self.headers.back()
+    }
+
+    pub(crate) fn hash_at(&self, height: u64) -> Option<BlockHash> {
+        let first = self.headers.front()?.height;
+        let index = usize::try_from(height.checked_sub(first)?).ok()?;
+        self.headers.get(index).map(|h| h.hash)
+    }
+
+    /// Whether `header` can be pushed on top of the tracked chain.
+    pub(crate) fn connects(&self, header: &BlockHeaderResponse) -> bool {
+        match self.tip() {
+            Some(tip) => tip.height + 1 == header.height && tip.hash == header.prev_hash,
+            None => true,
+        }
+    }
+
+    pub(crate) fn push(&mut self, header: BlockHeaderResponse) {
+        self.headers.push_back(header);
+        if self.headers.len() > MAX_REORG_DEPTH {
+            self.headers.pop_front();
+        }
+    }
+
+    /// Lowest tracked height, i.e. the deepest fork the tracker can rewind to.
+    pub(crate) fn lowest_height(&self) -> Option<u64> {
+        self.headers.front().map(|h| h.height)
+    }
+
+    /// Remove and return the blocks above `fork_height`, in ascending order.
+    pub(crate) fn rewind(&mut self, fork_height: u64) -> Vec<BlockHeaderResponse> {
+        let keep = self
+            .headers
+            .iter()
+            .take_while(|h| h.height <= fork_height)
+            .count();
+        self.headers.split_off(keep).into_iter().collect()
+    }
+}
+
+/// Source of the block headers of the block stream, implemented for [`DaemonJsonRpcClient`].
+pub(crate) trait HeaderSource {
+    fn block_header(
+        &self,
+        selector: GetBlockHeaderSelector,
+    ) -> BoxFuture<'_, anyhow::Result<BlockHeaderResponse>>;
+
+    fn block_headers_range(
+        &self,
+        range: RangeInclusive<u64>,
+    ) -> BoxFuture<'_, anyhow::Result<Vec<BlockHeaderResponse>>>;
+}
+
+impl HeaderSource for DaemonJsonRpcClient {
+    fn block_header(
+        &self,
+        selector: GetBlockHeaderSelector,
+    ) -> BoxFuture<'_, anyhow::Result<BlockHeaderResponse>> {
+        self.get_block_header(selector).boxed()
+    }
+
+    fn block_headers_range(
+        &self,
+        range: RangeInclusive<u64>,
+    ) -> BoxFuture<'_, anyhow::Result<Vec<BlockHeaderResponse>>> {
+        async move { Ok(self.get_block_headers_range(range).await?.0) }.boxed()
+    }
+}
+
+struct BlockStreamState<D> {
+    daemon: D,
+    next_height: Option<u64>,
+    chain: ChainTracker,
+}
+
+impl<D: HeaderSource> BlockStreamState<D> {
+    fn new(daemon: D, start_height: Option<u64>) -> Self {
+        Self {
+            daemon,
+            next_height: start_height,
+            chain: ChainTracker::default(),
+        }
+    }
+
+    /// Return the events since the last poll, fetching at most one range of
+    /// [`RESTRICTED_BLOCK_HEADERS_RANGE_LIMIT`] blocks.
+    async fn poll(&mut self) -> anyhow::Result<Vec<BlockEvent>> {
+        let top = self
+            .daemon
+            .block_header(GetBlockHeaderSelector::Last)
+            .await?;
+
+        if let Some(tip) = self.chain.tip() {
+            if tip.hash == top.hash {
+                return Ok(vec![]);
+            }
+            if top.height <= tip.height {
+                return Ok(vec![self.handle_reorg(top.height).await?]);
+            }
+        }
+
+        let start = match self.next_height {
+            Some(height) => height,
+            None => top.height,
+        };
+        if start > top.height {
+            return Ok(vec![]);
+        }
+        let end = top
+            .height
+            .min(start.saturating_add(RESTRICTED_BLOCK_HEADERS_RANGE_LIMIT - 1));
+
+        let headers = self.daemon.block_headers_range(start..=end).await?;
+
+        let mut events = vec![];
+        for (i, header) in headers.into_iter().enumerate() {
+            if !self.chain.connects(&header) {
+                if i == 0 {
+                    return Ok(vec![self.handle_reorg(top.height).await?]);
+                }
+                // the chain changed while fetching; the next poll sees the reorg
+                debug!(
+                    "Block {} does not connect to the tracked chain",
+                    header.height
+                );
+                break;
+            }
+            self.next_height = Some(header.height + 1);
+            self.chain.push(header.clone());
+            events.push(BlockEvent::Block(header));
+        }
+
+        Ok(events)
+    }
+
+    async fn handle_reorg(&mut self, top_height: u64) -> anyhow::Result<BlockEvent> {
+        let lowest = self.chain.lowest_height().unwrap_or_default();
+        // the daemon chain may be shorter than ours after a reorg
+        let mut height = self
+            .chain
+            .tip()
+            .map_or(top_height, |tip| tip.height.min(top_height));
+
+        loop {
+            let canonical = self
+                .daemon
+                .block_header(GetBlockHeaderSelector::Height(height))
+                .await?;
+
+            if Some(canonical.hash) == self.chain.hash_at(height) {
+                break;
+            }
+            if height <= lowest {
+                // no later poll can connect to the tracked chain, so start again from the top
+                self.chain = ChainTracker::default();
+                self.next_height = None;
+                anyhow::bail!(
+                    "Reorg deeper than {} blocks, cannot find the fork point",
+                    MAX_REORG_DEPTH
+                );
+            }
+            height -= 1;
+        }
+
+        let orphaned = self.chain.rewind(height);
+        warn!(
+            "Reorg detected at height {}, {} blocks orphaned",
+            height,
+            orphaned.len()
+        );
+        self.next_height = Some(height + 1);
+
+        Ok(BlockEvent::Reorg {
+            fork_height: height,
+            orphaned,
+        })
+    }
+}
+
+/// Stream of [`DaemonJsonRpcClient::block_stream`], over any [`HeaderSource`].
+fn block_stream<D: HeaderSource + Send + Sync>(
+    daemon: D,
+    start_height: Option<u64>,
+    poll_interval: Duration,
+) -> impl Stream<Item = anyhow::Result<BlockEvent>> + Send {
+    let state = Arc::new(Mutex::new(BlockStreamState::new(daemon, start_height)));
+
+    poll_stream(poll_interval, move || {
+        let state = state.clone();
+        async move { state.lock().await.poll().await }
+    })
+}
+
+impl DaemonJsonRpcClient {
+    /// Stream the blocks of the chain, polling the daemon every `poll_interval`.
+    ///
+    /// Starts at `start_height`, or at the current top block if `None`, and yields every block in
+    /// order; a `start_height` far below the top is caught up without waiting, one range of
+    /// [`RESTRICTED_BLOCK_HEADERS_RANGE_LIMIT`] blocks at a time. The `prev_hash` of each block is
+    /// checked against the previous block, and a [`BlockEvent::Reorg`] is yielded when the daemon
+    /// switches to another chain. Errors are yielded as they happen and polling goes on
+    /// afterwards; after a reorg deeper than [`MAX_REORG_DEPTH`] blocks, whose fork point cannot
+    /// be found, the stream starts again from the top block.
+    pub fn block_stream(
+        &self,
+        start_height: Option<u64>,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = anyhow::Result<BlockEvent>> + Send {
+        block_stream(self.clone(), start_height, poll_interval)
+    }
+}
+
+/// Item type of [`DaemonRpcClient::tx_pool_stream`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TxPoolEvent {
+    /// A transaction entered the pool.
+    Added(Transaction),
+    /// A transaction left the pool without being mined.
+    Removed(CryptoNoteHash),
+    /// A transaction left the pool and was mined at the given height.
+    Confirmed(CryptoNoteHash, u64),
+    /// The daemon saw a double spend attempt of a transaction in the pool.
+    DoubleSpendSeen(CryptoNoteHash),
+}
+
+/// Transactions of the pool seen by the pool stream, with their `double_spend_seen` flag.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TxPoolTracker {
+    txs: HashMap<CryptoNoteHash, bool>,
+}
+
+impl TxPoolTracker {
+    /// Split the current pool listing into the new transactions, and the known transactions to
+    /// look up again: those that left the pool, and, only when the daemon counts more
+    /// `num_double_spends` in the pool than the tracked flagged ones, those not flagged yet.
+    pub(crate) fn diff(
+        &self,
+        pool: &[CryptoNoteHash],
+        num_double_spends: u32,
+    ) -> (Vec<CryptoNoteHash>, Vec<CryptoNoteHash>) {
+        let pool_set: HashSet<_> = pool.iter().collect();
+        let flagged = self
+            .txs
+            .iter()
+            .filter(|(hash, double_spend_seen)| **double_spend_seen && pool_set.contains(hash))
+            .count();
+        let recheck_pool = num_double_spends as usize > flagged;
+
+        let added = pool
+            .iter()
+            .filter(|hash| !self.txs.contains_key(hash))
+            .cloned()
+            .collect();
+        let mut recheck: Vec<_> = self
+            .txs
+            .iter()
+            .filter(|(hash, double_spend_seen)| {
+                !pool_set.contains(hash) || (recheck_pool && !**double_spend_seen)
+            })
+            .map(|(hash, _)| *hash)
+            .collect();
+        recheck.sort();
+
+        (added, recheck)
+    }
+
+    /// Process the lookup of the transactions returned by [`Self::diff`].
+    pub(crate) fn update(
+        &mut self,
+        added: Vec<Transaction>,
+        rechecked: Vec<Transaction>,
+        missed: Vec<CryptoNoteHash>,
+    ) -> Vec<TxPoolEvent> {
+        let mut events = vec![];
+
+        for tx in added {
+            let hash = tx.tx_hash.0;
+            events.push(TxPoolEvent::Added(tx.clone()));
+            self.txs.insert(hash, false);
+            self.check(tx, &mut events);
+        }
+        for tx in rechecked {
+            self.check(tx, &mut events);
+        }
+        for hash in missed {
+            if self.txs.remove(&hash).is_some() {
+                events.push(TxPoolEvent::Removed(hash));
+            }
+        }
+
+        events
+    }
+
+    fn check(&mut self, tx: Transaction, events: &mut Vec<TxPoolEvent>) {
+        let hash = tx.tx_hash.0;
+
+        if !tx.in_pool {
+            self.txs.remove(&hash);
+            events.push(match tx.block_height {
+                Some(height) => TxPoolEvent::Confirmed(hash, height),
+                None => TxPoolEvent::Removed(hash),
+            });
+            return;
+        }
+
+        if let Some(double_spend_seen) = self.txs.get_mut(&hash) {
+            if tx.double_spend_seen && !*double_spend_seen {
+                *double_spend_seen = true;
+                events.push(TxPoolEvent::DoubleSpendSeen(hash));
+            }
+        }
+    }
+}
+
+struct TxPoolStreamState {
+    daemon: DaemonRpcClient,
+    pool: TxPoolTracker,
+}
+
+impl TxPoolStreamState {
+    /// Return the events since the last poll.
+    async fn poll(&mut self) -> anyhow::Result<Vec<TxPoolEvent>> {
+        let pool: Vec<_> = self
+            .daemon
+            .get_transaction_pool_hashes()
+            .await?
+            .tx_hashes
+            .into_iter()
+            .map(|hash| hash.0)
+            .collect();
+        let stats = self.daemon.get_transaction_pool_stats().await?;
+        let (added, recheck) = self.pool.diff(&pool, stats.num_double_spends);
+
+        // new transactions that left the pool between the listing and the lookup are missed, and
+        // are not tracked; missed known transactions are yielded as removed
+        let (added, _) = self.get_transactions(added, false).await?;
+        let (rechecked, missed) = self.get_transactions(recheck, true).await?;
+
+        Ok(self.pool.update(added, rechecked, missed))
+    }
+
+    /// Look up transactions, returning the found and the missed ones.
+    async fn get_transactions(
+        &self,
+        txs_hashes: Vec<CryptoNoteHash>,
+        prune: bool,
+    ) -> anyhow::Result<(Vec<Transaction>, Vec<CryptoNoteHash>)> {
+        if txs_hashes.is_empty() {
+            return Ok((vec![], vec![]));
+        }
+
+        let rsp = self
+            .daemon
+            .get_transactions_chunked(
+                txs_hashes,
+                None,
+                Some(prune),
+                RESTRICTED_GET_TRANSACTIONS_LIMIT,
+                1,
+            )
+            .await?;
+        let missed = rsp
+            .missed_tx
+            .unwrap_or_default()
+            .into_iter()
+            .map(|hash| hash.0)
+            .collect();
+
+        Ok((rsp.txs.unwrap_or_default(), missed))
+    }
+}
+
+impl DaemonRpcClient {
+    /// Watch the transaction pool, polling the daemon every `poll_interval`.
+    ///
+    /// The transactions already in the pool are yielded first as [`TxPoolEvent::Added`]. A
+    /// [`TxPoolEvent::DoubleSpendSeen`] is yielded once for each transaction whose
+    /// `double_spend_seen` flag gets set, so risky 0-conf payments can be flagged. Errors are
+    /// yielded as they happen and polling goes on afterwards, so the stream never ends on its own.
+    pub fn tx_pool_stream(
+        &self,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = anyhow::Result<TxPoolEvent>> + Send {
+        let state = Arc::new(Mutex::new(TxPoolStreamState {
+            daemon: self.clone(),
+            pool: TxPoolTracker::default(),
+        }));
+
+        poll_stream(poll_interval, move || {
+            let state = state.clone();
+            async move { state.lock().await.poll().await }
+        })
+    }
+}
+
+/// Item type of [`WalletClient::transfer_stream`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TransferEvent {
+    /// An incoming transfer entered the pool.
+    IncomingPool(GotTransfer),
+    /// An incoming transfer, or a coinbase output, was mined.
+    IncomingConfirmed(GotTransfer),
+    /// An outgoing transfer was created, pending or already mined.
+    Outgoing(GotTransfer),
+    /// An outgoing transfer failed.
+    Failed(GotTransfer),
+    /// The confirmations of an incoming or outgoing transfer changed.
+    ConfirmationsChanged(GotTransfer),
+}
+
+/// Deduplication key of wallet transfers: txid and subaddress index.
+type TransferKey = (Vec<u8>, u32, u32);
+
+/// Transfers seen by the transfer stream, with their last category and confirmations.
+#[derive(Clone, Debug)]
+pub(crate) struct TransferTracker {
+    seen: HashMap<TransferKey, (GetTransfersCategory, TransferHeight, Option<u64>)>,
+    confirmations_to_track: u64,
+}
+
+impl TransferTracker {
+    pub(crate) fn new(confirmations_to_track: u64) -> Self {
+        Self {
+            seen: HashMap::new(),
+            confirmations_to_track,
+        }
+    }
+
+    /// Exclusive minimum height of the next `get_transfers` call: the wallet height, or lower if
+    /// some mined transfers have less than `confirmations_to_track`.
+    pub(crate) fn min_height(&self, wallet_height: u64) -> u64 {
+        self.seen
+            .values()
+            .filter_map(|(_, height, confirmations)| match height {
+                TransferHeight::Confirmed(height)
+                    if confirmations.unwrap_or_default() < self.confirmations_to_track =>
+                {
+                    Some(height.get() - 1)
+                }
+                _ => None,
+            })
+            .chain(Some(wallet_height.saturating_sub(1)))
+            .min()
+            .unwrap_or_default()
+    }
+
+    /// Process the result of `get_transfers`, called with `min_height` as exclusive minimum.
+    pub(crate) fn update(
+        &mut self,
+        transfers: HashMap<GetTransfersCategory, Vec<GotTransfer>>,
+        min_height: u64,
+    ) -> Vec<TransferEvent> {
+        let mut transfers: Vec<_> = transfers.into_values().flatten().collect();
+        transfers.sort_by(|a, b| {
+            let height = |t: &GotTransfer| match t.height {
+                TransferHeight::Confirmed(height) => height.get(),
+                TransferHeight::InPool => u64::MAX,
+            };
+            (height(a), a.timestamp, &a.txid.0).cmp(&(height(b), b.timestamp, &b.txid.0))
+        });
+
+        let mut events = vec![];
+        let mut returned = HashSet::new();
+
+        for transfer in transfers {
+            let key = (
+                transfer.txid.0.clone(),
+                transfer.subaddr_index.major,
+                transfer.subaddr_index.minor,
+            );
+            let previous = self.seen.insert(
+                key.clone(),
+                (
+                    transfer.transfer_type.clone(),
+                    transfer.height.clone(),
+                    transfer.confirmations,
+                ),
+            );
+            returned.insert(key);
+
+            use crate::GetTransfersCategory::*;
+            // coinbase outputs are incoming transfers of type `block`
+            let event: Option<fn(GotTransfer) -> TransferEvent> =
+                match (&transfer.transfer_type, previous) {
+                    (Failed, Some((Failed, ..))) => None,
+                    (Failed, _) => Some(TransferEvent::Failed),
+                    (Pool, None) => Some(TransferEvent::IncomingPool),
+                    (In, None) | (In, Some((Pool, ..))) | (Block, None) => {
+                        Some(TransferEvent::IncomingConfirmed)
+                    }
+                    (Out, None) | (Pending, None) => Some(TransferEvent::Outgoing),
+                    (In, Some((_, _, confirmations)))
+                    | (Block, Some((_, _, confirmations)))
+                    | (Out, Some((_, _, confirmations)))
+                        if confirmations != transfer.confirmations
+                            && confirmations.unwrap_or_default() < self.confirmations_to_track =>
+                    {
+                        Some(TransferEvent::ConfirmationsChanged)
+                    }
+                    _ => None,
+                };
+            if let Some(event) = event {
+                events.push(event(transfer));
+            }
+        }
+
+        // pool transfers are always returned, and mined ones above `min_height`
+        self.seen.retain(|key, (_, height, _)| match height {
+            TransferHeight::InPool => returned.contains(key),
+            TransferHeight::Confirmed(height) => height.get() > min_height,
+        });
+
+        events
+    }
+}
+
+struct TransferStreamState {
+    wallet: WalletClient,
+    account_index: Option<u32>,
+    min_height: Option<u64>,
+    transfers: TransferTracker,
+}
+
+impl TransferStreamState {
+    /// Return the events since the last poll.
+    async fn poll(&mut self) -> anyhow::Result<Vec<TransferEvent>> {
+        self.wallet.refresh(None).await?;
+        let wallet_height = self.wallet.get_height().await?.get();
+
+        let min_height = match self.min_height {
+            Some(height) => height,
+            None => self.transfers.min_height(wallet_height),
+        };
+
+        let category_selector = [
+            GetTransfersCategory::In,
+            GetTransfersCategory::Out,
+            GetTransfersCategory::Pending,
+            GetTransfersCategory::Failed,
+            GetTransfersCategory::Pool,
+        ]
+        .iter()
+        .map(|category| (category.clone(), true))
+        .collect();
+        let transfers = self
+            .wallet
+            .get_transfers(GetTransfersSelector {
+                category_selector,
+                account_index: self.account_index,
+                subaddr_indices: None,
+                block_height_filter: Some(BlockHeightFilter {
+                    min_height: Some(min_height),
+                    max_height: None,
+                }),
+            })
+            .await?;
+
+        let events = self.transfers.update(transfers, min_height);
+        self.min_height = Some(self.transfers.min_height(wallet_height));
+
+        Ok(events)
+    }
+}
+
+impl WalletClient {
+    /// Stream the new and updated transfers of the wallet, refreshing it and polling
+    /// `get_transfers` every `poll_interval`.
+    ///
+    /// Starts with the transfers mined after `start_height`, or at the current wallet height if
+    /// `None`, and the pending and pool transfers. Transfers are deduplicated by txid and
+    /// subaddress index, and [`TransferEvent::ConfirmationsChanged`] is yielded until they reach
+    /// `confirmations_to_track`. Errors are yielded as they happen and polling goes on afterwards,
+    /// so the stream never ends on its own.
+    pub fn transfer_stream(
+        &self,
+        account_index: Option<u32>,
+        start_height: Option<u64>,
+        confirmations_to_track: u64,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = anyhow::Result<TransferEvent>> + Send {
+        let state = Arc::new(Mutex::new(TransferStreamState {
+            wallet: self.clone(),
+            account_index,
+            min_height: start_height,
+            transfers: TransferTracker::new(confirmations_to_track),
+        }));
+
+        poll_stream(poll_interval, move || {
+            let state = state.clone();
+            async move { state.lock().await.poll().await }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{util::test_address, HashString};
+    use chrono::prelude::*;
+    use futures::StreamExt;
+    use monero::Amount;
+    use std::sync::Mutex as StdMutex;
+
+    fn header(height: u64, byte: u8, prev_byte: u8) -> BlockHeaderResponse {
+        BlockHeaderResponse {
+            block_size: 85,
+            depth: 0,
+            difficulty: 1,
+            hash: BlockHash::repeat_byte(byte),
+            height,
+            major_version: 16,
+            minor_version: 16,
+            nonce: 0,
+            num_txes: 0,
+            orphan_status: false,
+            prev_hash: BlockHash::repeat_byte(prev_byte),
+            reward: Amount::from_pico(0),
+            timestamp: DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(0, 0), Utc),
+        }
+    }
+
+    #[tokio::test]
+    async fn poll_stream_drains_events_then_waits() {
+        let polls = Arc::new(StdMutex::new(VecDeque::from(vec![
+            Ok(vec![1, 2]),
+            Ok(vec![3]),
+            Ok(vec![]),
+            Err(anyhow::anyhow!("daemon down")),
+            Ok(vec![4]),
+        ])));
+        let calls = polls.clone();
+        let stream = poll_stream(Duration::from_millis(1), move || {
+            let next = calls.lock().unwrap().pop_front();
+            futures::future::ready(next.unwrap_or_else(|| Ok(vec![])))
+        });
+        futures::pin_mut!(stream);
+
+        let mut items = vec![];
+        for _ in 0..5 {
+            items.push(stream.next().await.unwrap().map_err(|e| e.to_string()));
+        }
+        assert_eq!(
+            items,
+            vec![Ok(1), Ok(2), Ok(3), Err("daemon down".to_string()), Ok(4)]
+        );
+        assert!(polls.lock().unwrap().is_empty());
+
+        // empty polls are spaced by the poll interval
+        let count = Arc::new(StdMutex::new(0));
+        let calls = count.clone();
+        let stream = poll_stream::<u8, _, _>(Duration::from_millis(20), move || {
+            *calls.lock().unwrap() += 1;
+            futures::future::ready(Ok(vec![]))
+        });
+        futures::pin_mut!(stream);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), stream.next())
+                .await
+                .is_err()
+        );
+        assert!((1..=3).contains(&*count.lock().unwrap()));
+    }
+
+    #[test]
+    fn chain_tracker_connects() {
+        let mut chain = ChainTracker::default();
+        assert!(chain.connects(&header(10, 1, 0)));
+
+        chain.push(header(10, 1, 0));
+        assert!(chain.connects(&header(11, 2, 1)));
+        assert!(!chain.connects(&header(11, 2, 3)));
+        assert!(!chain.connects(&header(12, 2, 1)));
+        assert!(!chain.connects(&header(10, 2, 1)));
+    }
+
+    #[test]
+    fn chain_tracker_hash_at_and_rewind() {
+        let mut chain = ChainTracker::default();
+        for height in 10..15 {
+            chain.push(header(height, height as u8, height as u8 - 1));
+        }
+
+        assert_eq!(chain.hash_at(9), None);
+        assert_eq!(chain.hash_at(12), Some(BlockHash::repeat_byte(12)));
+        assert_eq!(chain.hash_at(15), None);
+        assert_eq!(chain.lowest_height(), Some(10));
+
+        let orphaned = chain.rewind(12);
+        assert_eq!(orphaned, vec![header(13, 13, 12), header(14, 14, 13)]);
+        assert_eq!(chain.tip(), Some(&header(12, 12, 11)));
+        assert!(chain.connects(&header(13, 42, 12)));
+
+        assert_eq!(chain.rewind(20), vec![]);
+        assert_eq!(chain.rewind(0).len(), 3);
+        assert_eq!(chain.tip(), None);
+    }
+
+    #[test]
+    fn chain_tracker_keeps_max_reorg_depth_blocks() {
+        let mut chain = ChainTracker::default();
+        for height in 0..(MAX_REORG_DEPTH as u64 + 10) {
+            chain.push(header(height, height as u8, height.wrapping_sub(1) as u8));
+        }
+
+        assert_eq!(chain.lowest_height(), Some(10));
+        assert_eq!(
+            chain.tip().map(|t| t.height),
+            Some(MAX_REORG_DEPTH as u64 + 9)
+        );
+    }
+
+    /// Chain of `len` blocks, whose blocks above `fork_height` are replaced by the blocks of
+    /// another chain.
+    fn chain(len: u64, fork_height: u64) -> Vec<BlockHeaderResponse> {
+        let hash = |height: u64| {
+            let other_chain = if height > fork_height { 1 << 32 } else { 0 };
+            BlockHash::from_low_u64_be(height + other_chain + 1)
+        };
+        (0..len)
+            .map(|height| BlockHeaderResponse {
+                hash: hash(height),
+                prev_hash: height.checked_sub(1).map_or_else(BlockHash::zero, hash),
+                ..header(height, 0, 0)
+            })
+            .collect()
+    }
+
+    /// Daemon serving the headers of a chain that tests can replace.
+    #[derive(Clone)]
+    struct StubDaemon(Arc<StdMutex<Vec<BlockHeaderResponse>>>);
+
+    impl HeaderSource for StubDaemon {
+        fn block_header(
+            &self,
+            selector: GetBlockHeaderSelector,
+        ) -> BoxFuture<'_, anyhow::Result<BlockHeaderResponse>> {
+            let chain = self.0.lock().unwrap();
+            let header = match selector {
+                GetBlockHeaderSelector::Last => chain.last().cloned(),
+                GetBlockHeaderSelector::Height(height) => chain.get(height as usize).cloned(),
+                GetBlockHeaderSelector::Hash(hash) => {
+                    chain.iter().find(|h| h.hash == hash).cloned()
+                }
+            };
+            futures::future::ready(header.ok_or_else(|| anyhow::anyhow!("Unknown block"))).boxed()
+        }
+
+        fn block_headers_range(
+            &self,
+            range: RangeInclusive<u64>,
+        ) -> BoxFuture<'_, anyhow::Result<Vec<BlockHeaderResponse>>> {
+            let chain = self.0.lock().unwrap();
+            let headers = chain
+                .iter()
+                .filter(|h| range.contains(&h.height))
+                .cloned()
+                .collect();
+            futures::future::ready(Ok(headers)).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn block_stream_catches_up_one_range_per_poll() {
+        let daemon = StubDaemon(Arc::new(StdMutex::new(chain(2500, u64::MAX))));
+        let mut state = BlockStreamState::new(daemon, Some(0));
+
+        assert_eq!(state.poll().await.unwrap().len(), 1000);
+        assert_eq!(state.poll().await.unwrap().len(), 1000);
+        let events = state.poll().await.unwrap();
+        assert_eq!(events.len(), 500);
+        assert_eq!(
+            events.last(),
+            Some(&BlockEvent::Block(chain(2500, u64::MAX)[2499].clone()))
+        );
+        assert_eq!(state.poll().await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn block_stream_reorg() {
+        let daemon = StubDaemon(Arc::new(StdMutex::new(chain(5, u64::MAX))));
+        let mut stream = Box::pin(block_stream(
+            daemon.clone(),
+            Some(0),
+            Duration::from_millis(1),
+        ));
+        for header in chain(5, u64::MAX) {
+            assert_eq!(
+                stream.next().await.unwrap().unwrap(),
+                BlockEvent::Block(header)
+            );
+        }
+
+        // blocks 3 and 4 are replaced, and block 5 is added
+        *daemon.0.lock().unwrap() = chain(6, 2);
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            BlockEvent::Reorg {
+                fork_height: 2,
+                orphaned: chain(5, u64::MAX)[3..].to_vec(),
+            }
+        );
+        for header in chain(6, 2).into_iter().skip(3) {
+            assert_eq!(
+                stream.next().await.unwrap().unwrap(),
+                BlockEvent::Block(header)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn block_stream_restarts_on_deep_reorg() {
+        let daemon = StubDaemon(Arc::new(StdMutex::new(chain(150, u64::MAX))));
+        let mut stream = Box::pin(block_stream(
+            daemon.clone(),
+            Some(0),
+            Duration::from_millis(1),
+        ));
+        for _ in 0..150 {
+            stream.next().await.unwrap().unwrap();
+        }
+
+        // only the blocks below the tracked chain are kept
+        *daemon.0.lock().unwrap() = chain(150, 10);
+        assert_eq!(
+            stream.next().await.unwrap().unwrap_err().to_string(),
+            format!(
+                "Reorg deeper than {} blocks, cannot find the fork point",
+                MAX_REORG_DEPTH
+            )
+        );
+        // the stream goes on from the top block of the new chain
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            BlockEvent::Block(chain(150, 10)[149].clone())
+        );
+    }
+
+    fn pool_tx(
+        byte: u8,
+        in_pool: bool,
+        block_height: Option<u64>,
+        double_spend_seen: bool,
+    ) -> Transaction {
+        Transaction {
+            as_hex: "".to_string(),
+            as_json: None,
+            block_height,
+            block_timestamp: None,
+            confirmations: None,
+            double_spend_seen,
+            in_pool,
+            output_indices: None,
+            prunable_hash: None,
+            pruned_as_hex: None,
+            received_timestamp: None,
+            relayed: None,
+            tx_hash: HashString(CryptoNoteHash::repeat_byte(byte)),
+        }
+    }
+
+    #[test]
+    fn tx_pool_tracker_diff() {
+        let mut pool = TxPoolTracker::default();
+        let hash = CryptoNoteHash::repeat_byte;
+
+        assert_eq!(
+            pool.diff(&[hash(1), hash(2)], 0),
+            (vec![hash(1), hash(2)], vec![])
+        );
+
+        pool.update(
+            vec![pool_tx(1, true, None, false), pool_tx(2, true, None, true)],
+            vec![],
+            vec![],
+        );
+        // 3 is new, and 1 is only rechecked when the daemon counts more double spends than 2
+        assert_eq!(
+            pool.diff(&[hash(1), hash(2), hash(3)], 1),
+            (vec![hash(3)], vec![])
+        );
+        assert_eq!(
+            pool.diff(&[hash(1), hash(2), hash(3)], 2),
+            (vec![hash(3)], vec![hash(1)])
+        );
+        // both left the pool
+        assert_eq!(pool.diff(&[], 0), (vec![], vec![hash(1), hash(2)]));
+    }
+
+    #[test]
+    fn tx_pool_tracker_update() {
+        let mut pool = TxPoolTracker::default();
+        let hash = CryptoNoteHash::repeat_byte;
+
+        let events = pool.update(
+            vec![
+                pool_tx(1, true, None, false),
+                pool_tx(2, true, None, true),
+                pool_tx(3, false, Some(10), false),
+            ],
+            vec![],
+            vec![],
+        );
+        assert_eq!(
+            events,
+            vec![
+                TxPoolEvent::Added(pool_tx(1, true, None, false)),
+                TxPoolEvent::Added(pool_tx(2, true, None, true)),
+                TxPoolEvent::DoubleSpendSeen(hash(2)),
+                TxPoolEvent::Added(pool_tx(3, false, Some(10), false)),
+                TxPoolEvent::Confirmed(hash(3), 10),
+            ]
+        );
+
+        let events = pool.update(
+            vec![],
+            vec![pool_tx(1, true, None, true), pool_tx(2, true, None, true)],
+            vec![],
+        );
+        assert_eq!(events, vec![TxPoolEvent::DoubleSpendSeen(hash(1))]);
+
+        let events = pool.update(
+            vec![],
+            vec![pool_tx(1, false, Some(11), true)],
+            vec![hash(2), hash(3)],
+        );
+        assert_eq!(
+            events,
+            vec![
+                TxPoolEvent::Confirmed(hash(1), 11),
+                TxPoolEvent::Removed(hash(2))
+            ]
+        );
+        assert_eq!(pool.diff(&[], 0), (vec![], vec![]));
+    }
+
+    fn transfer(
+        txid: u8,
+        minor: u32,
+        transfer_type: GetTransfersCategory,
+        height: u64,
+        confirmations: Option<u64>,
+    ) -> GotTransfer {
+        GotTransfer {
+            address: test_address(1, monero::Network::Mainnet),
+            amount: Amount::from_pico(1),
+            confirmations,
+            double_spend_seen: false,
+            fee: Amount::from_pico(0),
+            height: match std::num::NonZeroU64::new(height) {
+                Some(height) => TransferHeight::Confirmed(height),
+                None => TransferHeight::InPool,
+            },
+            note: "".to_string(),
+            payment_id: HashString(monero::util::address::PaymentId::zero()),
+            subaddr_index: monero::cryptonote::subaddress::Index { major: 0, minor },
+            suggested_confirmations_threshold: 1,
+            timestamp: DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(0, 0), Utc),
+            txid: HashString(vec![txid; 32]),
+            transfer_type,
+            unlock_time: 0,
+        }
+    }
+
+    fn by_category(transfers: Vec<GotTransfer>) -> HashMap<GetTransfersCategory, Vec<GotTransfer>> {
+        let mut map = HashMap::new();
+        for transfer in transfers {
+            map.entry(transfer.transfer_type.clone())
+                .or_insert_with(Vec::new)
+                .push(transfer);
+        }
+        map
+    }
+
+    #[test]
+    fn transfer_tracker_update() {
+        use GetTransfersCategory::*;
+
+        let mut tracker = TransferTracker::new(2);
+        assert_eq!(tracker.min_height(100), 99);
+
+        let events = tracker.update(
+            by_category(vec![
+                transfer(1, 0, Pool, 0, Some(0)),
+                transfer(1, 1, Pool, 0, Some(0)),
+                transfer(2, 0, Pending, 0, Some(0)),
+                transfer(3, 0, In, 100, Some(1)),
+            ]),
+            99,
+        );
+        assert_eq!(
+            events,
+            vec![
+                TransferEvent::IncomingConfirmed(transfer(3, 0, In, 100, Some(1))),
+                TransferEvent::IncomingPool(transfer(1, 0, Pool, 0, Some(0))),
+                TransferEvent::IncomingPool(transfer(1, 1, Pool, 0, Some(0))),
+                TransferEvent::Outgoing(transfer(2, 0, Pending, 0, Some(0))),
+            ]
+        );
+        // transfer 3 needs one more confirmation
+        assert_eq!(tracker.min_height(101), 99);
+
+        // same result: nothing new
+        let events = tracker.update(
+            by_category(vec![
+                transfer(1, 0, Pool, 0, Some(0)),
+                transfer(1, 1, Pool, 0, Some(0)),
+                transfer(2, 0, Pending, 0, Some(0)),
+                transfer(3, 0, In, 100, Some(1)),
+            ]),
+            99,
+        );
+        assert_eq!(events, vec![]);
+
+        let events = tracker.update(
+            by_category(vec![
+                transfer(1, 0, In, 101, Some(1)),
+                transfer(2, 0, Failed, 0, Some(0)),
+                transfer(3, 0, In, 100, Some(2)),
+            ]),
+            99,
+        );
+        assert_eq!(
+            events,
+            vec![
+                TransferEvent::ConfirmationsChanged(transfer(3, 0, In, 100, Some(2))),
+                TransferEvent::IncomingConfirmed(transfer(1, 0, In, 101, Some(1))),
+                TransferEvent::Failed(transfer(2, 0, Failed, 0, Some(0))),
+            ]
+        );
+        // transfer 1 at index 1 left the pool without being mined, and is forgotten
+        assert_eq!(tracker.seen.len(), 3);
+        assert_eq!(tracker.min_height(102), 100);
+
+        // transfer 3 reached 2 confirmations, later changes are ignored
+        let events = tracker.update(
+            by_category(vec![
+                transfer(1, 0, In, 101, Some(2)),
+                transfer(3, 0, In, 100, Some(3)),
+            ]),
+            100,
+        );
+        assert_eq!(
+            events,
+            vec![TransferEvent::ConfirmationsChanged(transfer(
+                1,
+                0,
+                In,
+                101,
+                Some(2)
+            ))]
+        );
+        assert_eq!(tracker.min_height(103), 102);
+    }
+}
@@ -86,6 +86,27 @@ where
     }
 }
 
+/// Key pair number `i` of the unit tests, with distinct view and spend keys.
+#[cfg(test)]
+pub(crate) fn test_key_pair(i: u8) -> monero::KeyPair {
+    let key = |kind| {
+        let mut key = [0; 32];
+        key[0] = i;
+        key[1] = kind;
+        monero::PrivateKey::from_slice(&key).unwrap()
+    };
+    monero::KeyPair {
+        view: key(1),
+        spend: key(2),
+    }
+}
+
+/// Address of [`test_key_pair`] `i` on `network`.
+#[cfg(test)]
+pub(crate) fn test_address(i: u8, network: monero::Network) -> monero::Address {
+    monero::Address::from_keypair(network, &test_key_pair(i))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
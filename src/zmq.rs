@@ -0,0 +1,514 @@
+// Copyright 2019-2022 Artem Vorotnikov and Monero Rust Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    BlockHash, EncryptedAmount, HashString, JsonEcdhInfo, JsonRctSignatures, JsonTransaction,
+    JsonTxIn, JsonTxOut, JsonTxOutTarget, KeyImage, RctKey, ViewTag,
+};
+use futures::{stream, Stream};
+use monero::{cryptonote::hash::Hash as CryptoNoteHash, util::amount, Amount};
+use serde::{Deserialize, Deserializer};
+use tracing::*;
+use zeromq::{Socket, SocketRecv, SubSocket};
+
+/// Topics published by monerod with `--zmq-pub`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ZmqTopic {
+    /// `json-minimal-chain_main`, see [`ZmqEvent::MinimalChainMain`].
+    MinimalChainMain,
+    /// `json-full-chain_main`, see [`ZmqEvent::FullChainMain`].
+    FullChainMain,
+    /// `json-minimal-txpool_add`, see [`ZmqEvent::MinimalTxPoolAdd`].
+    MinimalTxPoolAdd,
+}
+
+impl ZmqTopic {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ZmqTopic::MinimalChainMain => "json-minimal-chain_main",
+            ZmqTopic::FullChainMain => "json-full-chain_main",
+            ZmqTopic::MinimalTxPoolAdd => "json-minimal-txpool_add",
+        }
+    }
+}
+
+/// Item type of [`ZmqSubscriber`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ZmqEvent {
+    /// Blocks added to the main chain. After a reorg, `first_height` is below the previous top.
+    MinimalChainMain(ZmqMinimalChain),
+    /// Same as [`ZmqEvent::MinimalChainMain`], with the content of the blocks.
+    FullChainMain(Vec<ZmqBlock>),
+    /// Transactions added to the pool.
+    MinimalTxPoolAdd(Vec<ZmqTxPoolAdd>),
+}
+
+impl ZmqEvent {
+    /// Parse a message published by monerod, formatted as `<topic>:<json>`.
+    pub fn from_message(message: &[u8]) -> anyhow::Result<Self> {
+        let separator = message
+            .iter()
+            .position(|b| *b == b':')
+            .ok_or_else(|| anyhow::Error::msg("Missing topic in ZMQ message"))?;
+        let (topic, json) = (&message[..separator], &message[separator + 1..]);
+
+        Ok(match topic {
+            b"json-minimal-chain_main" => ZmqEvent::MinimalChainMain(serde_json::from_slice(json)?),
+            b"json-full-chain_main" => ZmqEvent::FullChainMain(serde_json::from_slice(json)?),
+            b"json-minimal-txpool_add" => ZmqEvent::MinimalTxPoolAdd(serde_json::from_slice(json)?),
+            _ => anyhow::bail!("Unsupported ZMQ topic: {}", String::from_utf8_lossy(topic)),
+        })
+    }
+}
+
+/// Payload of `json-minimal-chain_main`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct ZmqMinimalChain {
+    pub first_height: u64,
+    pub first_prev_id: HashString<BlockHash>,
+    /// Hashes of the blocks, starting at `first_height`.
+    pub ids: Vec<HashString<BlockHash>>,
+}
+
+/// Sub-type of [`ZmqEvent::FullChainMain`].
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct ZmqBlock {
+    pub major_version: u8,
+    pub minor_version: u8,
+    pub timestamp: u64,
+    pub prev_id: HashString<BlockHash>,
+    pub nonce: u32,
+    #[serde(deserialize_with = "deserialize_zmq_transaction")]
+    pub miner_tx: JsonTransaction,
+    pub tx_hashes: Vec<HashString<CryptoNoteHash>>,
+}
+
+/// Sub-type of [`ZmqEvent::MinimalTxPoolAdd`].
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct ZmqTxPoolAdd {
+    pub id: HashString<CryptoNoteHash>,
+    pub blob_size: u64,
+    pub weight: u64,
+    #[serde(with = "amount::serde::as_pico")]
+    pub fee: Amount,
+}
+
+// monerod serializes transactions differently over ZMQ than in `get_transactions`, so they are
+// read with the types below, then converted into the `JsonTransaction` model.
+
+#[derive(Deserialize)]
+struct ZmqTransaction {
+    version: u64,
+    unlock_time: u64,
+    inputs: Vec<ZmqTxIn>,
+    outputs: Vec<ZmqTxOut>,
+    extra: HashString<Vec<u8>>,
+    /// Omitted for pruned transactions.
+    #[serde(default)]
+    signatures: Vec<Vec<HashString<Vec<u8>>>>,
+    ringct: Option<ZmqRctSig>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ZmqTxIn {
+    Gen {
+        height: u64,
+    },
+    ToKey {
+        #[serde(with = "amount::serde::as_pico")]
+        amount: Amount,
+        key_offsets: Vec<u64>,
+        key_image: HashString<KeyImage>,
+    },
+}
+
+#[derive(Deserialize)]
+struct ZmqTxOut {
+    #[serde(with = "amount::serde::as_pico")]
+    amount: Amount,
+    #[serde(flatten)]
+    target: ZmqTxOutTarget,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ZmqTxOutTarget {
+    ToKey {
+        key: HashString<RctKey>,
+    },
+    ToTaggedKey {
+        key: HashString<RctKey>,
+        view_tag: HashString<ViewTag>,
+    },
+}
+
+/// Only the non-prunable part is read, coinbase transactions have no prunable part.
+#[derive(Deserialize)]
+struct ZmqRctSig {
+    #[serde(rename = "type")]
+    rct_type: u8,
+    #[serde(default)]
+    encrypted: Vec<ZmqEcdhTuple>,
+    #[serde(default)]
+    commitments: Vec<HashString<RctKey>>,
+    #[serde(default, with = "amount::serde::as_pico::opt")]
+    fee: Option<Amount>,
+}
+
+#[derive(Deserialize)]
+struct ZmqEcdhTuple {
+    mask: HashString<RctKey>,
+    amount: HashString<RctKey>,
+}
+
+/// First RingCT type using compact `ecdhInfo`, i.e. `Bulletproof2`.
+const RCT_TYPE_BULLETPROOF2: u8 = 4;
+
+impl From<ZmqTransaction> for JsonTransaction {
+    fn from(tx: ZmqTransaction) -> Self {
+        let vin = tx
+            .inputs
+            .into_iter()
+            .map(|input| match input {
+                ZmqTxIn::Gen { height } => JsonTxIn::Gen { height },
+                ZmqTxIn::ToKey {
+                    amount,
+                    key_offsets,
+                    key_image,
+                } => JsonTxIn::Key {
+                    amount,
+                    key_offsets,
+                    k_image: key_image,
+                },
+            })
+            .collect();
+        let vout = tx
+            .outputs
+            .into_iter()
+            .map(|output| JsonTxOut {
+                amount: output.amount,
+                target: match output.target {
+                    ZmqTxOutTarget::ToKey { key } => JsonTxOutTarget::Key(key),
+                    ZmqTxOutTarget::ToTaggedKey { key, view_tag } => {
+                        JsonTxOutTarget::TaggedKey { key, view_tag }
+                    }
+                },
+            })
+            .collect();
+        let signatures = tx
+            .signatures
+            .into_iter()
+            .map(|ring| HashString(ring.into_iter().flat_map(|sig| sig.0).collect()))
+            .collect();
+        let rct_signatures = tx.ringct.map(|rct| {
+            let rct_type = rct.rct_type;
+            let ecdh_info = rct
+                .encrypted
+                .into_iter()
+                .map(|tuple| {
+                    if rct_type >= RCT_TYPE_BULLETPROOF2 {
+                        JsonEcdhInfo::Compact {
+                            amount: HashString(EncryptedAmount::from_slice(
+                                &tuple.amount.0.as_bytes()[..8],
+                            )),
+                        }
+                    } else {
+                        JsonEcdhInfo::Standard {
+                            mask: tuple.mask,
+                            amount: tuple.amount,
+                        }
+                    }
+                })
+                .collect();
+
+            JsonRctSignatures {
+                rct_type,
+                // like `get_transactions`, coinbase transactions have no fee
+                txn_fee: rct.fee.filter(|_| rct_type != 0),
+                pseudo_outs: vec![],
+                ecdh_info,
+                out_pk: rct.commitments,
+            }
+        });
+
+        JsonTransaction {
+            version: tx.version,
+            unlock_time: tx.unlock_time,
+            vin,
+            vout,
+            extra: tx.extra.0,
+            signatures,
+            rct_signatures,
+            rctsig_prunable: None,
+        }
+    }
+}
+
+fn deserialize_zmq_transaction<'de, D>(deserializer: D) -> Result<JsonTransaction, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(ZmqTransaction::deserialize(deserializer)?.into())
+}
+
+/// Subscriber to the ZMQ notifications of monerod, started with `--zmq-pub`.
+///
+/// ```rust,no_run
+/// use monero_rpc::{ZmqSubscriber, ZmqTopic};
+///
+/// # async fn run() -> anyhow::Result<()> {
+/// let mut subscriber =
+///     ZmqSubscriber::connect("tcp://127.0.0.1:18083", &[ZmqTopic::MinimalChainMain]).await?;
+/// let event = subscriber.recv().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ZmqSubscriber {
+    socket: SubSocket,
+}
+
+impl ZmqSubscriber {
+    /// Connect to `endpoint` and subscribe to `topics`.
+    pub async fn connect(endpoint: &str, topics: &[ZmqTopic]) -> anyhow::Result<Self> {
+        let mut socket = SubSocket::new();
+        socket.connect(endpoint).await?;
+        for topic in topics {
+            socket.subscribe(topic.as_str()).await?;
+        }
+
+        Ok(Self { socket })
+    }
+
+    /// Wait for the next event.
+    pub async fn recv(&mut self) -> anyhow::Result<ZmqEvent> {
+        let message = self.socket.recv().await?;
+        let message: Vec<u8> = message.into_vec().concat();
+
+        trace!(
+            "Received ZMQ message: {:?}",
+            String::from_utf8_lossy(&message)
+        );
+
+        ZmqEvent::from_message(&message)
+    }
+
+    /// Turn the subscriber into a stream of events, that never ends on its own.
+    pub fn into_stream(self) -> impl Stream<Item = anyhow::Result<ZmqEvent>> + Send {
+        stream::unfold(self, |mut subscriber| async move {
+            let event = subscriber.recv().await;
+            Some((event, subscriber))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use zeromq::{PubSocket, SocketSend, ZmqMessage};
+
+    fn block_hash(byte: u8) -> HashString<BlockHash> {
+        HashString(BlockHash::repeat_byte(byte))
+    }
+
+    #[test]
+    fn parse_minimal_chain_main() {
+        let message = format!(
+            r#"json-minimal-chain_main:{{"first_height":1000,"first_prev_id":"{}","ids":["{}","{}"]}}"#,
+            "01".repeat(32),
+            "02".repeat(32),
+            "03".repeat(32)
+        );
+
+        assert_eq!(
+            ZmqEvent::from_message(message.as_bytes()).unwrap(),
+            ZmqEvent::MinimalChainMain(ZmqMinimalChain {
+                first_height: 1000,
+                first_prev_id: block_hash(1),
+                ids: vec![block_hash(2), block_hash(3)],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_full_chain_main() {
+        let message = format!(
+            r#"json-full-chain_main:[{{"major_version":16,"minor_version":16,"timestamp":1660000000,"prev_id":"{}","nonce":42,"miner_tx":{{"version":2,"unlock_time":1060,"inputs":[{{"gen":{{"height":1000}}}}],"outputs":[{{"amount":600000000000,"to_tagged_key":{{"key":"{}","view_tag":"d6"}}}}],"extra":"01{}","signatures":[],"ringct":{{"type":0,"encrypted":[],"commitments":[],"fee":0}}}},"tx_hashes":["{}"]}}]"#,
+            "01".repeat(32),
+            "aa".repeat(32),
+            "bb".repeat(32),
+            "cc".repeat(32)
+        );
+
+        let mut extra = vec![1];
+        extra.extend([0xbb; 32]);
+        assert_eq!(
+            ZmqEvent::from_message(message.as_bytes()).unwrap(),
+            ZmqEvent::FullChainMain(vec![ZmqBlock {
+                major_version: 16,
+                minor_version: 16,
+                timestamp: 1660000000,
+                prev_id: block_hash(1),
+                nonce: 42,
+                miner_tx: JsonTransaction {
+                    version: 2,
+                    unlock_time: 1060,
+                    vin: vec![JsonTxIn::Gen { height: 1000 }],
+                    vout: vec![JsonTxOut {
+                        amount: Amount::from_pico(600000000000),
+                        target: JsonTxOutTarget::TaggedKey {
+                            key: HashString(RctKey::repeat_byte(0xaa)),
+                            view_tag: HashString(ViewTag::repeat_byte(0xd6)),
+                        },
+                    }],
+                    extra,
+                    signatures: vec![],
+                    rct_signatures: Some(JsonRctSignatures {
+                        rct_type: 0,
+                        txn_fee: None,
+                        pseudo_outs: vec![],
+                        ecdh_info: vec![],
+                        out_pk: vec![],
+                    }),
+                    rctsig_prunable: None,
+                },
+                tx_hashes: vec![HashString(CryptoNoteHash([0xcc; 32]))],
+            }])
+        );
+    }
+
+    #[test]
+    fn convert_zmq_transaction() {
+        let json = format!(
+            r#"{{"version":2,"unlock_time":0,"inputs":[{{"to_key":{{"amount":0,"key_offsets":[10,2],"key_image":"{}"}}}}],"outputs":[{{"amount":0,"to_key":{{"key":"{}"}}}}],"extra":"","ringct":{{"type":6,"encrypted":[{{"mask":"{}","amount":"{}"}}],"commitments":["{}"],"fee":30000000}}}}"#,
+            "01".repeat(32),
+            "02".repeat(32),
+            "00".repeat(32),
+            "03".repeat(8) + &"00".repeat(24),
+            "04".repeat(32)
+        );
+        let tx: JsonTransaction = serde_json::from_str::<ZmqTransaction>(&json)
+            .unwrap()
+            .into();
+
+        assert_eq!(
+            tx.vin,
+            vec![JsonTxIn::Key {
+                amount: Amount::from_pico(0),
+                key_offsets: vec![10, 2],
+                k_image: HashString(KeyImage::repeat_byte(1)),
+            }]
+        );
+        assert_eq!(
+            tx.vout[0].target,
+            JsonTxOutTarget::Key(HashString(RctKey::repeat_byte(2)))
+        );
+        assert_eq!(
+            tx.rct_signatures,
+            Some(JsonRctSignatures {
+                rct_type: 6,
+                txn_fee: Some(Amount::from_pico(30000000)),
+                pseudo_outs: vec![],
+                ecdh_info: vec![JsonEcdhInfo::Compact {
+                    amount: HashString(EncryptedAmount::repeat_byte(3)),
+                }],
+                out_pk: vec![HashString(RctKey::repeat_byte(4))],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_minimal_txpool_add() {
+        let message = format!(
+            r#"json-minimal-txpool_add:[{{"id":"{}","blob_size":1533,"weight":1533,"fee":27790000}}]"#,
+            "fa".repeat(32)
+        );
+
+        assert_eq!(
+            ZmqEvent::from_message(message.as_bytes()).unwrap(),
+            ZmqEvent::MinimalTxPoolAdd(vec![ZmqTxPoolAdd {
+                id: HashString(CryptoNoteHash([0xfa; 32])),
+                blob_size: 1533,
+                weight: 1533,
+                fee: Amount::from_pico(27790000),
+            }])
+        );
+    }
+
+    #[test]
+    fn parse_invalid_messages() {
+        assert_eq!(
+            ZmqEvent::from_message(b"json-minimal-chain_main")
+                .unwrap_err()
+                .to_string(),
+            "Missing topic in ZMQ message"
+        );
+        assert_eq!(
+            ZmqEvent::from_message(b"json-full-txpool_add:[]")
+                .unwrap_err()
+                .to_string(),
+            "Unsupported ZMQ topic: json-full-txpool_add"
+        );
+        assert!(ZmqEvent::from_message(b"json-minimal-txpool_add:{}").is_err());
+    }
+
+    #[tokio::test]
+    async fn subscribe_to_local_publisher() {
+        let mut publisher = PubSocket::new();
+        let endpoint = publisher.bind("tcp://127.0.0.1:0").await.unwrap();
+
+        let mut subscriber =
+            ZmqSubscriber::connect(&endpoint.to_string(), &[ZmqTopic::MinimalTxPoolAdd])
+                .await
+                .unwrap();
+
+        let not_subscribed = format!(
+            r#"json-minimal-chain_main:{{"first_height":1,"first_prev_id":"{}","ids":[]}}"#,
+            "01".repeat(32)
+        );
+        let subscribed = format!(
+            r#"json-minimal-txpool_add:[{{"id":"{}","blob_size":1,"weight":1,"fee":1}}]"#,
+            "fa".repeat(32)
+        );
+
+        // the subscription reaches the publisher asynchronously, so publish until received
+        let event = loop {
+            publisher
+                .send(ZmqMessage::from(not_subscribed.clone()))
+                .await
+                .unwrap();
+            publisher
+                .send(ZmqMessage::from(subscribed.clone()))
+                .await
+                .unwrap();
+
+            if let Ok(event) =
+                tokio::time::timeout(Duration::from_millis(100), subscriber.recv()).await
+            {
+                break event.unwrap();
+            }
+        };
+
+        assert_eq!(
+            event,
+            ZmqEvent::MinimalTxPoolAdd(vec![ZmqTxPoolAdd {
+                id: HashString(CryptoNoteHash([0xfa; 32])),
+                blob_size: 1,
+                weight: 1,
+                fee: Amount::from_pico(1),
+            }])
+        );
+    }
+}
@@ -10,8 +10,9 @@ use monero::{
 use monero_rpc::{
     BalanceData, BlockHeightFilter, GetTransfersCategory, GetTransfersSelector, GotTransfer,
     HashString, IncomingTransfer, IncomingTransfers, KeyImageImportResponse, Payment,
-    PrivateKeyType, SubaddressBalanceData, SweepAllArgs, Transaction, TransactionsResponse,
-    TransferHeight, TransferOptions, TransferPriority, TransferType,
+    PrivateKeyType, ReserveProofAmount, SignatureType, SignedTxSet, SubaddressBalanceData,
+    SweepAllArgs, SweepOptions, Transaction, TransactionsResponse, TransferHeight, TransferOptions,
+    TransferPriority, TransferType, TxKey, TxKeyCheck, TxProofCheck, UnsignedTxSet,
 };
 
 use super::helpers;
@@ -261,12 +262,63 @@ pub async fn run() {
     )
     .await;
 
+    // reserve proofs of wallet_2, for all its balance and for part of its primary account
+    let wallet_2_reserve_proof = helpers::wallet::reserve_proof_assert_check(
+        &wallet,
+        ReserveProofAmount::All,
+        wallet_2_address,
+        expected_balance,
+    )
+    .await;
+    helpers::wallet::reserve_proof_assert_check(
+        &wallet,
+        ReserveProofAmount::Account {
+            account_index: 0,
+            amount: Amount::from_xmr(1.0).unwrap(),
+        },
+        wallet_2_address,
+        // wallet_2 has a single output, which covers the amount
+        expected_balance,
+    )
+    .await;
+    helpers::wallet::audit_reserve_proofs_assert_reserves(
+        &wallet,
+        wallet_2_reserve_proof,
+        expected_balance,
+    )
+    .await;
+
+    // message signatures of wallet_2, with its spend and view keys, and of its subaddress
+    helpers::wallet::sign_assert_verify(
+        &wallet,
+        "login nonce",
+        Index { major: 0, minor: 0 },
+        wallet_2_address,
+        SignatureType::Spend,
+    )
+    .await;
+    helpers::wallet::sign_assert_verify(
+        &wallet,
+        "login nonce",
+        Index { major: 0, minor: 0 },
+        wallet_2_address,
+        SignatureType::View,
+    )
+    .await;
+    helpers::wallet::sign_assert_verify(
+        &wallet,
+        "login nonce",
+        Index { major: 0, minor: 1 },
+        wallet_2_subaddress_1,
+        SignatureType::Spend,
+    )
+    .await;
+
     // STEP 4: we test the interaction between wallets by creating transfers between different
     // wallets, and between different addresses in the same wallet.
 
     // transfers and transactions
-    let mut transfer_1_destination: HashMap<Address, Amount> = HashMap::new();
-    transfer_1_destination.insert(wallet_1_address, Amount::from_xmr(5.0).unwrap());
+    let mut transfer_1_destination = vec![(wallet_1_address, Amount::from_xmr(5.0).unwrap())];
 
     let mut transfer_options = TransferOptions {
         account_index: None,
@@ -276,9 +328,13 @@ pub async fn run() {
         unlock_time: None,
         payment_id: None,
         do_not_relay: None,
+        subtract_fee_from_outputs: None,
+        get_tx_key: None,
+        get_tx_hex: None,
+        get_tx_metadata: None,
     };
 
-    transfer_1_destination.insert(wallet_2_subaddress_1, Amount::from_xmr(40.0).unwrap());
+    transfer_1_destination.push((wallet_2_subaddress_1, Amount::from_xmr(40.0).unwrap()));
     helpers::wallet::transfer_error_invalid_balance(
         &wallet,
         transfer_1_destination.clone(),
@@ -287,14 +343,12 @@ pub async fn run() {
     .await;
 
     // change to an amount that fits in the balance...
-    transfer_1_destination
-        .entry(wallet_2_subaddress_1)
-        .and_modify(|e| *e = Amount::from_xmr(10.0).unwrap());
+    transfer_1_destination[1].1 = Amount::from_xmr(10.0).unwrap();
 
     // ... but add an invalid address ...
     let wallet_3_testnet_address =
         Address::from_keypair(Network::Testnet, &helpers::get_keypair_1());
-    transfer_1_destination.insert(wallet_3_testnet_address, Amount::from_xmr(40.0).unwrap());
+    transfer_1_destination.push((wallet_3_testnet_address, Amount::from_xmr(40.0).unwrap()));
     helpers::wallet::transfer_error_invalid_address(
         &wallet,
         transfer_1_destination.clone(),
@@ -302,11 +356,15 @@ pub async fn run() {
         wallet_3_testnet_address,
     )
     .await;
+    helpers::wallet::transfer_error_wrong_network(
+        transfer_1_destination.clone(),
+        transfer_options.clone(),
+        wallet_3_testnet_address,
+    )
+    .await;
 
     // ... remove the invalid address but add a 'wrong' account_index...
-    transfer_1_destination
-        .remove(&wallet_3_testnet_address)
-        .unwrap();
+    transfer_1_destination.pop().unwrap();
     transfer_options.account_index = Some(10);
     helpers::wallet::transfer_error_invalid_balance(
         &wallet,
@@ -339,15 +397,14 @@ pub async fn run() {
     // ... try to relay it again...
     helpers::wallet::relay_tx_assert_tx_hash(
         &wallet,
-        transfer_1_data.tx_metadata.to_string(),
+        transfer_1_data.tx_metadata.0.clone(),
         transfer_1_data.tx_hash.0.to_string(),
     )
     .await;
 
     // relay_tx errors
-    helpers::wallet::relay_tx_error_invalid_hex(&wallet, "01234".to_string()).await;
-    let mut wrong_tx_metadata = transfer_1_data.tx_metadata.to_string();
-    wrong_tx_metadata.replace_range(100..105, "6");
+    let mut wrong_tx_metadata = transfer_1_data.tx_metadata.0.clone();
+    wrong_tx_metadata.0.drain(50..52);
     helpers::wallet::relay_tx_error_invalid_tx_metadata(&wallet, wrong_tx_metadata).await;
 
     // obsolete payment ids
@@ -359,6 +416,10 @@ pub async fn run() {
         unlock_time: Some(20),
         payment_id: Some(PaymentId::zero()),
         do_not_relay: Some(true),
+        subtract_fee_from_outputs: None,
+        get_tx_key: None,
+        get_tx_hex: None,
+        get_tx_metadata: None,
     };
     helpers::wallet::transfer_error_payment_id_obsolete(
         &wallet,
@@ -367,6 +428,44 @@ pub async fn run() {
     )
     .await;
 
+    // not relayed: two outputs to the same address...
+    helpers::wallet::transfer_assert_ok(
+        &wallet,
+        vec![
+            (wallet_1_address, Amount::from_xmr(1.0).unwrap()),
+            (wallet_1_address, Amount::from_xmr(2.0).unwrap()),
+        ],
+        TransferOptions {
+            do_not_relay: Some(true),
+            ..Default::default()
+        },
+        TransferPriority::Default,
+    )
+    .await;
+    // ... the fee paid by the first destination, without the transaction hex and metadata...
+    helpers::wallet::transfer_assert_fee_subtracted(
+        &wallet,
+        vec![
+            (wallet_1_address, Amount::from_xmr(1.0).unwrap()),
+            (wallet_2_subaddress_1, Amount::from_xmr(2.0).unwrap()),
+        ],
+    )
+    .await;
+
+    // ... and transfer_split, with two outputs to the same address
+    helpers::wallet::transfer_split_assert_ok(
+        &wallet,
+        vec![
+            (wallet_2_address, Amount::from_xmr(1.0).unwrap()),
+            (wallet_2_address, Amount::from_xmr(2.0).unwrap()),
+        ],
+        TransferOptions {
+            do_not_relay: Some(true),
+            ..Default::default()
+        },
+    )
+    .await;
+
     // STEP 5: we test what was generated by transactions. That is, we get
     // the transactions created in different formats, check getting the transactions
     // from the blockchain using its hash, check the transaction keys, export and import key
@@ -390,7 +489,13 @@ pub async fn run() {
                 tx_hash: transfer_1_data.tx_hash.clone(),
                 block_height: None,
                 block_timestamp: None,
+                confirmations: None,
                 output_indices: None,
+                relayed: Some(true),
+                // the three fields below are not tested since they vary
+                prunable_hash: None,
+                pruned_as_hex: None,
+                received_timestamp: None,
             }]),
             txs_as_hex: Some(vec![transfer_1_data.tx_blob.0.encode_hex()]),
             txs_as_json: None,
@@ -414,7 +519,13 @@ pub async fn run() {
                 tx_hash: transfer_1_data.tx_hash.clone(),
                 block_height: None,
                 block_timestamp: None,
+                confirmations: None,
                 output_indices: None,
+                relayed: Some(true),
+                // the three fields below are not tested since they vary
+                prunable_hash: None,
+                pruned_as_hex: None,
+                received_timestamp: None,
             }]),
             txs_as_hex: Some(vec!["".to_string()]),
             txs_as_json: None,
@@ -434,6 +545,21 @@ pub async fn run() {
     )
     .await;
 
+    helpers::daemon_rpc::get_transaction_pool_hashes_assert_contains(
+        &daemon_rpc,
+        transfer_1_data.tx_hash.0,
+    )
+    .await;
+    helpers::daemon_rpc::tx_pool_stream_assert_added(&daemon_rpc, transfer_1_data.tx_hash.0).await;
+
+    helpers::wallet::transfer_stream_assert_outgoing(&wallet, transfer_1_data.tx_hash.0).await;
+    helpers::wallet::payment_processor_create_invoice_assert_unpaid(&wallet).await;
+
+    // nothing mines the transaction, so waiting for it times out
+    helpers::wait_for_confirmations_error_timeout_in_pool(&daemon_rpc, transfer_1_data.tx_hash.0)
+        .await;
+    helpers::wait_for_confirmations_error_timeout_in_pool(&wallet, transfer_1_data.tx_hash.0).await;
+
     // get_transfer
     let expected_got_transfer = Some(GotTransfer {
         address: wallet_2_address,
@@ -473,7 +599,11 @@ pub async fn run() {
         transfer_1_data.tx_hash.0,
         transfer_1_data.tx_key.0.clone(),
         wallet_1_address,
-        (0, true, transfer_1_destination[&wallet_1_address]),
+        TxKeyCheck {
+            confirmations: 0,
+            in_pool: true,
+            received: transfer_1_destination[0].1,
+        },
     )
     .await;
     helpers::wallet::check_tx_key_assert_confirmations_in_pool_status_received_amount(
@@ -483,12 +613,12 @@ pub async fn run() {
         wallet_2_address,
         // wallet_2 has just one output of value expected_balance;
         // it uses such outout in the transaction
-        // thus, the last value of the tuple is the change
-        (
-            0,
-            true,
-            expected_balance - transfer_1_data.amount - transfer_1_data.fee,
-        ),
+        // thus, the received amount is the change
+        TxKeyCheck {
+            confirmations: 0,
+            in_pool: true,
+            received: expected_balance - transfer_1_data.amount - transfer_1_data.fee,
+        },
     )
     .await;
     helpers::wallet::check_tx_key_error_invalid_txid(
@@ -501,7 +631,7 @@ pub async fn run() {
     helpers::wallet::check_tx_key_error_invalid_tx_key(
         &wallet,
         transfer_1_data.tx_hash.0,
-        vec![1, 2, 3, 4],
+        TxKey(vec![1, 2, 3, 4]),
         wallet_2_address,
     )
     .await;
@@ -513,6 +643,29 @@ pub async fn run() {
     )
     .await;
 
+    // get_tx_key, and the proofs that do not reveal it
+    helpers::wallet::get_tx_key_assert_tx_key(
+        &wallet,
+        transfer_1_data.tx_hash.0,
+        transfer_1_data.tx_key.0.clone(),
+    )
+    .await;
+    helpers::wallet::get_tx_key_error_invalid_txid(&wallet, Hash::zero()).await;
+    helpers::wallet::tx_proof_assert_check(
+        &wallet,
+        transfer_1_data.tx_hash.0,
+        wallet_1_address,
+        Some("proof of payment".to_string()),
+        TxProofCheck {
+            good: true,
+            confirmations: 0,
+            in_pool: true,
+            received: transfer_1_destination[0].1,
+        },
+    )
+    .await;
+    helpers::wallet::spend_proof_assert_good(&wallet, transfer_1_data.tx_hash.0, None).await;
+
     // export_key_images for wallet_2...
     // should be empty
     helpers::wallet::export_key_images_empty_assert_ok(&wallet).await;
@@ -613,7 +766,7 @@ pub async fn run() {
     let expected_key_image_import_response = KeyImageImportResponse {
         height: height_before_settling_transfer_1,
         spent: Amount::from_pico(0),
-        unspent: transfer_1_destination[&wallet_1_address],
+        unspent: transfer_1_destination[0].1,
     };
 
     // ... first, for wallet_1_full...
@@ -646,8 +799,10 @@ pub async fn run() {
             global_index: 0, // this is any number, since we will not test against it
             key_image: None, // this is different from the key_image in the Inputs for transfer_1_data, so we set it to None and do not test it
             tx_size: None,   // any value, since we will not test againt it
-            amount: transfer_1_destination[&wallet_1_address],
+            amount: transfer_1_destination[0].1,
             spent: false,
+            frozen: Some(false),
+            unlocked: None, // depends on the height, so we do not test it
             subaddr_index: Index { major: 0, minor: 0 },
             tx_hash: transfer_1_data.tx_hash.clone(),
         }]),
@@ -671,6 +826,7 @@ pub async fn run() {
         expected_incoming_transfers,
     )
     .await;
+    helpers::wallet::freeze_thaw_assert_frozen(&wallet).await;
 
     // STEP 6: we create another transfer, but this time from a view-only wallet.
     // Since a view-only wallet cannot sign transactions, we then test signing the transaction
@@ -680,8 +836,7 @@ pub async fn run() {
     // wallet_1_view_only is read-only, so `transfer` will create an unsigned_txset, which is then used in `sign_transfer`...
     helpers::wallet::open_wallet_with_no_or_empty_password_assert_ok(&wallet, &wallet_1_view_only)
         .await;
-    let mut transfer_2_destination: HashMap<Address, Amount> = HashMap::new();
-    transfer_2_destination.insert(wallet_2_address, Amount::from_xmr(0.00001).unwrap());
+    let transfer_2_destination = vec![(wallet_2_address, Amount::from_xmr(0.00001).unwrap())];
     let transfer_2_data_unsigned = helpers::wallet::transfer_assert_ok(
         &wallet,
         transfer_2_destination,
@@ -693,6 +848,10 @@ pub async fn run() {
             unlock_time: None,
             payment_id: None,
             do_not_relay: None,
+            subtract_fee_from_outputs: None,
+            get_tx_key: None,
+            get_tx_hex: None,
+            get_tx_metadata: None,
         },
         TransferPriority::Unimportant,
     )
@@ -704,9 +863,10 @@ pub async fn run() {
         transfer_2_data_unsigned.unsigned_txset.0.clone(),
     )
     .await;
-    helpers::wallet::sign_transfer_error_cannot_load(&wallet, vec![0, 1, 2, 3]).await;
+    helpers::wallet::sign_transfer_error_cannot_load(&wallet, UnsignedTxSet(vec![0, 1, 2, 3]))
+        .await;
     let mut invalid_unsigned_txset = transfer_2_data_unsigned.unsigned_txset.0.clone();
-    for e in invalid_unsigned_txset.iter_mut().take(25 + 1).skip(20) {
+    for e in invalid_unsigned_txset.0.iter_mut().take(25 + 1).skip(20) {
         *e = 5;
     }
     helpers::wallet::sign_transfer_error_cannot_load(&wallet, invalid_unsigned_txset.clone()).await;
@@ -721,9 +881,9 @@ pub async fn run() {
         transfer_2_data_signed.signed_txset.clone(),
     )
     .await;
-    helpers::wallet::submit_transfer_error_parse(&wallet, vec![0, 1, 2, 3]).await;
+    helpers::wallet::submit_transfer_error_parse(&wallet, SignedTxSet(vec![0, 1, 2, 3])).await;
     let mut invalid_signed_txset = transfer_2_data_signed.signed_txset;
-    for e in invalid_signed_txset.iter_mut().take(25 + 1).skip(20) {
+    for e in invalid_signed_txset.0.iter_mut().take(25 + 1).skip(20) {
         *e = 5;
     }
     helpers::wallet::submit_transfer_error_parse(&wallet, invalid_signed_txset).await;
@@ -736,7 +896,7 @@ pub async fn run() {
         address: wallet_1_address,
         payment_id: HashString(PaymentId::zero()),
         tx_hash: transfer_1_data.tx_hash,
-        amount: transfer_1_destination[&wallet_1_address],
+        amount: transfer_1_destination[0].1,
         unlock_time: 0,
         subaddr_index: Index { major: 0, minor: 0 },
         block_height: height_before_settling_transfer_1,
@@ -839,38 +999,44 @@ pub async fn run() {
             address: wallet_2_address,
             account_index: 0,
             subaddr_indices: None,
+            subaddr_indices_all: None,
             priority: TransferPriority::Default,
             mixin: 0,
             ring_size: 0,
             unlock_time: 0,
-            get_tx_keys: None,
+            outputs: None,
             below_amount: None,
-            do_not_relay: None,
-            get_tx_hex: None,
-            get_tx_metadata: None,
+            options: SweepOptions::default(),
         },
     )
     .await;
 
+    helpers::wallet::sweep_single_error_unknown_key_image(&wallet, wallet_2_address).await;
+
     helpers::wallet::open_wallet_with_no_or_empty_password_assert_ok(&wallet, &wallet_2).await;
     // below is commented because it sometimes returns `true`, sometimes returns `false`
     // helpers::wallet::refresh(&wallet, Some(0), false).await;
     wallet.refresh(Some(0)).await.unwrap();
+    helpers::wallet::transfer_from_outputs_assert_others_thawed(&wallet, wallet_1_address).await;
     helpers::wallet::sweep_all_assert_ok(
         &wallet,
         SweepAllArgs {
             address: wallet_1_address,
             account_index: 0,
             subaddr_indices: Some(vec![0, 1, 2]),
+            subaddr_indices_all: None,
             priority: TransferPriority::Default,
             mixin: 5,
             ring_size: 10,
             unlock_time: 1,
+            outputs: Some(2),
             below_amount: Some(Amount::from_pico(100000000000000)),
-            do_not_relay: Some(false),
-            get_tx_keys: None,
-            get_tx_metadata: Some(false),
-            get_tx_hex: Some(true),
+            options: SweepOptions {
+                get_tx_keys: None,
+                do_not_relay: Some(false),
+                get_tx_hex: Some(true),
+                get_tx_metadata: Some(false),
+            },
         },
     )
     .await;
@@ -2,13 +2,13 @@ use std::str::FromStr;
 
 use super::helpers;
 use monero::cryptonote::hash::Hash;
-use monero_rpc::{HashString, TransactionsResponse};
+use monero_rpc::{HashString, TransactionPoolHashesResponse, TransactionsResponse};
 
 /*
 * The purpose of this test is to test functions from the `DaemonRpcClient`
 * (i.e, functions from https://www.getmonero.org/resources/developer-guides/daemon-rpc.html#other-daemon-rpc-calls).
 *
-* Currently, there is only one such function: `get_transactions`.
+* These are `get_transactions`, along with its chunked variant, and `get_transaction_pool_hashes`.
 * The scenarios tested in this test require that **no** transaction between two wallets
 * have been created. Note that coinbase wallets are insignificant for this test.
 *
@@ -59,4 +59,40 @@ pub async fn run() {
         expected_transactions_response,
     )
     .await;
+
+    // several chunks of non existent transactions are merged in order
+    let txs_hashes: Vec<Hash> = (1..=5).map(Hash::repeat_byte).collect();
+    let expected_transactions_response = TransactionsResponse {
+        credits: 0,
+        status: "OK".to_string(),
+        top_hash: "".to_string(),
+        untrusted: false,
+        missed_tx: Some(txs_hashes.iter().cloned().map(HashString).collect()),
+        txs: None,
+        txs_as_hex: None,
+        txs_as_json: None,
+    };
+
+    helpers::daemon_rpc::get_transactions_chunked_assert_response(
+        &daemon_rpc,
+        txs_hashes,
+        2,
+        2,
+        expected_transactions_response,
+    )
+    .await;
+
+    // nothing in the pool
+    helpers::daemon_rpc::get_transaction_pool_hashes_assert_response(
+        &daemon_rpc,
+        TransactionPoolHashesResponse {
+            credits: 0,
+            status: "OK".to_string(),
+            top_hash: "".to_string(),
+            tx_hashes: vec![],
+            untrusted: false,
+        },
+    )
+    .await;
+    helpers::daemon_rpc::get_transaction_pool_stats_assert_empty(&daemon_rpc).await;
 }
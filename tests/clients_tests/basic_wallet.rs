@@ -1,8 +1,12 @@
 use monero::{
     cryptonote::subaddress::{self, Index},
+    util::address::PaymentId,
     Address, Amount, Network, ViewPair,
 };
-use monero_rpc::{AddressData, GenerateFromKeysArgs, GetAccountsData, GotAccount, SubaddressData};
+use monero_rpc::{
+    AccountTag, AddressBookEntry, AddressData, GenerateFromKeysArgs, GetAccountsData, GotAccount,
+    SubaddressData,
+};
 
 use super::helpers;
 
@@ -295,5 +299,152 @@ pub async fn run() {
     )
     .await;
 
+    // accounts and their tags
+    let second_account_address = subaddress::get_subaddress(
+        &ViewPair::from(&key_pair_2),
+        subaddress::Index { major: 1, minor: 0 },
+        Some(Network::Mainnet),
+    );
+    helpers::wallet::create_account_assert_address_and_account_index(
+        &wallet,
+        Some("customer_1".to_string()),
+        (second_account_address, 1),
+    )
+    .await;
+    helpers::wallet::label_account_assert_ok(&wallet, 1, "customer_1_renamed".to_string()).await;
+    helpers::wallet::label_account_error_invalid_account_index(&wallet, 10).await;
+
+    helpers::wallet::get_account_tags_assert_account_tags(&wallet, vec![]).await;
+    helpers::wallet::tag_accounts_assert_ok(&wallet, "customers".to_string(), vec![1]).await;
+    helpers::wallet::set_account_tag_description_assert_ok(
+        &wallet,
+        "customers".to_string(),
+        "One account per customer".to_string(),
+    )
+    .await;
+    helpers::wallet::get_account_tags_assert_account_tags(
+        &wallet,
+        vec![AccountTag {
+            tag: "customers".to_string(),
+            label: "One account per customer".to_string(),
+            accounts: vec![1],
+        }],
+    )
+    .await;
+    helpers::wallet::get_accounts_assert_accounts_data(
+        &wallet,
+        Some("customers".to_string()),
+        GetAccountsData {
+            subaddress_accounts: vec![GotAccount {
+                account_index: 1,
+                balance: Amount::from_pico(0),
+                base_address: second_account_address,
+                label: Some("customer_1_renamed".to_string()),
+                tag: Some("customers".to_string()),
+                unlocked_balance: Amount::from_pico(0),
+            }],
+            total_balance: Amount::from_pico(0),
+            total_unlocked_balance: Amount::from_pico(0),
+        },
+    )
+    .await;
+
+    // once untagged, the tag has no account but is still registered
+    helpers::wallet::untag_accounts_assert_ok(&wallet, vec![1]).await;
+    helpers::wallet::get_account_tags_assert_account_tags(
+        &wallet,
+        vec![AccountTag {
+            tag: "customers".to_string(),
+            label: "One account per customer".to_string(),
+            accounts: vec![],
+        }],
+    )
+    .await;
+
+    // address book, with a standard and an integrated address
+    let book_address = Address::from_keypair(Network::Mainnet, &key_pair_1);
+    let book_payment_id = PaymentId::from_low_u64_be(42);
+    let book_integrated_address = Address::integrated(
+        Network::Mainnet,
+        book_address.public_spend,
+        book_address.public_view,
+        book_payment_id,
+    );
+    helpers::wallet::get_address_book_assert_entries(&wallet, vec![], vec![]).await;
+    helpers::wallet::add_address_book_assert_index(
+        &wallet,
+        book_address,
+        Some("payout_1".to_string()),
+        0,
+    )
+    .await;
+    helpers::wallet::add_address_book_assert_index(&wallet, book_integrated_address, None, 1).await;
+    let mut expected_entries = vec![
+        AddressBookEntry {
+            index: 0,
+            address: book_address,
+            description: "payout_1".to_string(),
+            payment_id: None,
+        },
+        AddressBookEntry {
+            index: 1,
+            address: book_integrated_address,
+            description: "".to_string(),
+            payment_id: Some(book_payment_id),
+        },
+    ];
+    helpers::wallet::get_address_book_assert_entries(&wallet, vec![], expected_entries.clone())
+        .await;
+    helpers::wallet::get_address_book_assert_entries(
+        &wallet,
+        vec![1],
+        vec![expected_entries[1].clone()],
+    )
+    .await;
+    helpers::wallet::get_address_book_error_invalid_index(&wallet, 10).await;
+
+    helpers::wallet::edit_address_book_assert_ok(&wallet, 1, None, Some("payout_2".to_string()))
+        .await;
+    expected_entries[1].description = "payout_2".to_string();
+    helpers::wallet::get_address_book_assert_entries(&wallet, vec![], expected_entries.clone())
+        .await;
+
+    // deleting the first entry shifts the index of the second one
+    helpers::wallet::delete_address_book_assert_ok(&wallet, 0).await;
+    expected_entries.remove(0);
+    expected_entries[0].index = 0;
+    helpers::wallet::get_address_book_assert_entries(&wallet, vec![], expected_entries).await;
+
+    // integrated addresses, made by the wallet and offline
+    helpers::wallet::make_integrated_address_assert_offline_address(
+        &wallet,
+        book_address,
+        book_payment_id,
+    )
+    .await;
+    helpers::wallet::make_integrated_address_random_payment_id_assert_ok(
+        &wallet,
+        Address::from_keypair(Network::Mainnet, &key_pair_2),
+    )
+    .await;
+    helpers::wallet::split_integrated_address_error_not_integrated(&wallet, book_address).await;
+
+    // address validation, by the wallet and offline
+    helpers::wallet::validate_address_assert_offline_validation(&wallet, book_address).await;
+    helpers::wallet::validate_address_assert_offline_validation(
+        &wallet,
+        Address::from_keypair(Network::Stagenet, &key_pair_2),
+    )
+    .await;
+    helpers::wallet::validate_address_assert_invalid(&wallet, "not an address".to_string()).await;
+
+    // clients bound to the network of the wallet, or to another one
+    helpers::wallet::with_network_assert_ok(Address::from_keypair(Network::Mainnet, &key_pair_2))
+        .await;
+    helpers::wallet::with_network_error_wrong_network().await;
+
+    helpers::wallet::is_multisig_assert_not_multisig(&wallet).await;
+    helpers::wallet::export_multisig_info_error_not_multisig(&wallet).await;
+
     helpers::wallet::close_wallet_assert_ok(&wallet).await;
 }
@@ -19,6 +19,12 @@ pub async fn run() {
     let genesis_block_hash = helpers::get_genesis_block_hash();
 
     helpers::regtest::get_block_count_assert_height(&regtest, 1).await;
+
+    // regtest daemons are on mainnet
+    let mainnet_regtest = helpers::setup_regtest_with_network(Network::Mainnet);
+    helpers::regtest::get_block_count_assert_height(&mainnet_regtest, 1).await;
+    let stagenet_regtest = helpers::setup_regtest_with_network(Network::Stagenet);
+    helpers::regtest::get_block_count_error_wrong_network(&stagenet_regtest).await;
     helpers::regtest::on_get_block_hash_error_invalid_height(&regtest, 10).await;
     helpers::regtest::on_get_block_hash_assert_hash(&regtest, 0, genesis_block_hash).await;
 
@@ -1,5 +1,28 @@
-use monero::cryptonote::hash::Hash;
-use monero_rpc::{DaemonRpcClient, TransactionsResponse};
+use std::time::Duration;
+
+use futures::StreamExt;
+use monero::cryptonote::hash::{Hash, Hashable};
+use monero::Amount;
+use monero_rpc::{
+    DaemonRpcClient, TransactionPoolHashesResponse, TransactionsResponse, TxPoolEvent,
+};
+
+// `received_timestamp`, `prunable_hash` and `pruned_as_hex` vary, so we do not test against them
+fn ignore_varying_fields(
+    transactions_response: &TransactionsResponse,
+    expected_transactions_response: &mut TransactionsResponse,
+) {
+    if let (Some(txs), Some(expected_txs)) = (
+        transactions_response.txs.as_ref(),
+        expected_transactions_response.txs.as_mut(),
+    ) {
+        for (tx, expected_tx) in txs.iter().zip(expected_txs.iter_mut()) {
+            expected_tx.received_timestamp = tx.received_timestamp;
+            expected_tx.prunable_hash = tx.prunable_hash.clone();
+            expected_tx.pruned_as_hex = tx.pruned_as_hex.clone();
+        }
+    }
+}
 
 pub async fn get_transactions_assert_response(
     daemon_rpc: &DaemonRpcClient,
@@ -13,10 +36,73 @@ pub async fn get_transactions_assert_response(
     assert_eq!(transactions_response, expected_transactions_response);
 }
 
-pub async fn get_transactions_as_hex_not_pruned_assert_response(
+pub async fn get_transaction_pool_hashes_assert_response(
+    daemon_rpc: &DaemonRpcClient,
+    expected_transaction_pool_hashes_response: TransactionPoolHashesResponse,
+) {
+    let transaction_pool_hashes_response = daemon_rpc.get_transaction_pool_hashes().await.unwrap();
+    assert_eq!(
+        transaction_pool_hashes_response,
+        expected_transaction_pool_hashes_response
+    );
+}
+
+pub async fn get_transaction_pool_stats_assert_empty(daemon_rpc: &DaemonRpcClient) {
+    let stats = daemon_rpc.get_transaction_pool_stats().await.unwrap();
+    assert_eq!(stats.txs_total, 0);
+    assert_eq!(stats.num_double_spends, 0);
+    assert_eq!(stats.fee_total, Amount::ZERO);
+}
+
+pub async fn get_transaction_pool_hashes_assert_contains(
+    daemon_rpc: &DaemonRpcClient,
+    tx_hash: Hash,
+) {
+    let transaction_pool_hashes_response = daemon_rpc.get_transaction_pool_hashes().await.unwrap();
+    assert!(transaction_pool_hashes_response
+        .tx_hashes
+        .iter()
+        .any(|hash| hash.0 == tx_hash));
+}
+
+pub async fn tx_pool_stream_assert_added(daemon_rpc: &DaemonRpcClient, tx_hash: Hash) {
+    let pool_size = daemon_rpc
+        .get_transaction_pool_hashes()
+        .await
+        .unwrap()
+        .tx_hashes
+        .len();
+    // the transactions already in the pool are yielded first
+    let events: Vec<TxPoolEvent> = daemon_rpc
+        .tx_pool_stream(Duration::from_millis(100))
+        .take(pool_size)
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    assert!(events.iter().any(|event| match event {
+        TxPoolEvent::Added(tx) => tx.tx_hash.0 == tx_hash && tx.in_pool,
+        _ => false,
+    }));
+}
+
+pub async fn get_transactions_chunked_assert_response(
     daemon_rpc: &DaemonRpcClient,
     txs_hashes: Vec<Hash>,
+    chunk_size: usize,
+    concurrency: usize,
     expected_transactions_response: TransactionsResponse,
+) {
+    let transactions_response = daemon_rpc
+        .get_transactions_chunked(txs_hashes, None, None, chunk_size, concurrency)
+        .await
+        .unwrap();
+    assert_eq!(transactions_response, expected_transactions_response);
+}
+
+pub async fn get_transactions_as_hex_not_pruned_assert_response(
+    daemon_rpc: &DaemonRpcClient,
+    txs_hashes: Vec<Hash>,
+    mut expected_transactions_response: TransactionsResponse,
 ) {
     let transactions_response_with_none = daemon_rpc
         .get_transactions(txs_hashes.clone(), None, None)
@@ -28,6 +114,24 @@ pub async fn get_transactions_as_hex_not_pruned_assert_response(
         .await
         .unwrap();
 
+    ignore_varying_fields(
+        &transactions_response_with_none,
+        &mut expected_transactions_response,
+    );
+
+    // full transactions can be hashed, and the hash must match the requested one
+    for tx in transactions_response_with_none.txs.as_ref().unwrap() {
+        assert_eq!(tx.decode_as_hex().unwrap().hash(), tx.tx_hash.0);
+    }
+    assert_eq!(
+        transactions_response_with_none
+            .decode_txs_as_hex()
+            .unwrap()
+            .unwrap()
+            .len(),
+        transactions_response_with_none.txs.as_ref().unwrap().len()
+    );
+
     assert_eq!(
         transactions_response_with_none,
         expected_transactions_response
@@ -41,17 +145,18 @@ pub async fn get_transactions_as_hex_not_pruned_assert_response(
 pub async fn get_transactions_as_hex_pruned_assert_response(
     daemon_rpc: &DaemonRpcClient,
     txs_hashes: Vec<Hash>,
-    expected_transactions_response: TransactionsResponse,
+    mut expected_transactions_response: TransactionsResponse,
 ) {
     let transactions_response = daemon_rpc
         .get_transactions(txs_hashes, None, Some(true))
         .await
         .unwrap();
+    ignore_varying_fields(&transactions_response, &mut expected_transactions_response);
     assert_eq!(transactions_response, expected_transactions_response);
 }
 
 fn test_tx_json_not_empty(transactions_response: TransactionsResponse) {
-    let txs_json = transactions_response.txs_as_json.unwrap();
+    let txs_json = transactions_response.decode_txs_as_json().unwrap().unwrap();
     if txs_json.is_empty() {
         panic!("txs_as_json should not be empty");
     }
@@ -59,6 +164,13 @@ fn test_tx_json_not_empty(transactions_response: TransactionsResponse) {
     let first_tx = &txs[0];
     let first_tx_as_json = first_tx.as_json.as_ref().unwrap();
     assert_ne!(first_tx_as_json, &"".to_string());
+
+    let first_tx_decoded = first_tx.decode_as_json().unwrap().unwrap();
+    assert_eq!(first_tx_decoded, txs_json[0]);
+    assert_eq!(first_tx_decoded.version, 2);
+    assert!(!first_tx_decoded.vin.is_empty());
+    assert!(!first_tx_decoded.vout.is_empty());
+    assert!(first_tx_decoded.rct_signatures.is_some());
 }
 
 pub async fn get_transactions_as_json_not_pruned_assert_response_not_empty(
@@ -1,6 +1,6 @@
-use monero::{KeyPair, PrivateKey};
-use monero_rpc::{BlockHash, RpcClient};
-use std::{env, str::FromStr};
+use monero::{Hash, KeyPair, Network, PrivateKey};
+use monero_rpc::{BlockHash, ConfirmationError, RpcClient, TxStatus, TxStatusSource};
+use std::{env, str::FromStr, time::Duration};
 
 pub mod daemon_rpc;
 pub mod regtest;
@@ -29,6 +29,41 @@ pub fn setup_monero() -> (
     (regtest, daemon_rpc, wallet)
 }
 
+/// The regtest daemon of [`setup_monero`], bound to `network`.
+pub fn setup_regtest_with_network(network: Network) -> monero_rpc::RegtestDaemonJsonRpcClient {
+    let dhost = env::var("MONERO_DAEMON_HOST").unwrap_or_else(|_| "localhost".into());
+
+    RpcClient::new(format!("http://{}:18081", dhost))
+        .with_network(network)
+        .daemon()
+        .regtest()
+}
+
+/// The wallet of [`setup_monero`], bound to `network`.
+pub fn setup_wallet_with_network(network: Network) -> monero_rpc::WalletClient {
+    let whost = env::var("MONERO_WALLET_HOST_1").unwrap_or_else(|_| "localhost".into());
+
+    RpcClient::new(format!("http://{}:18083", whost))
+        .with_network(network)
+        .wallet()
+}
+
+/// The wallet of [`setup_monero`], followed by the wallets of the second and third
+/// `monero-wallet-rpc` instances, which are only used by the multisig test.
+pub fn setup_multisig_wallets() -> Vec<monero_rpc::WalletClient> {
+    [
+        ("MONERO_WALLET_HOST_1", 18083),
+        ("MONERO_WALLET_HOST_2", 18084),
+        ("MONERO_WALLET_HOST_3", 18085),
+    ]
+    .iter()
+    .map(|(var, port)| {
+        let whost = env::var(var).unwrap_or_else(|_| "localhost".into());
+        RpcClient::new(format!("http://{}:{}", whost, port)).wallet()
+    })
+    .collect()
+}
+
 pub fn get_keypair_1() -> KeyPair {
     KeyPair {
         view: PrivateKey::from_str(
@@ -71,3 +106,25 @@ pub fn get_keypair_3() -> KeyPair {
 pub fn get_genesis_block_hash() -> BlockHash {
     BlockHash::from_str("418015bb9ae982a1975da7d79277c2705727a56894ba0fb246adaabb1f4632e3").unwrap()
 }
+
+pub async fn wait_for_confirmations_error_timeout_in_pool<S: TxStatusSource>(
+    source: &S,
+    txid: Hash,
+) {
+    let mut progress = vec![];
+    let err = monero_rpc::wait_for_confirmations(
+        source,
+        txid,
+        1,
+        Duration::from_millis(100),
+        Some(Duration::from_millis(500)),
+        |status| progress.push(status),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ConfirmationError>(),
+        Some(&ConfirmationError::Timeout(TxStatus::InPool))
+    );
+    assert_eq!(progress, vec![TxStatus::InPool]);
+}
@@ -1,9 +1,10 @@
-use std::ops::RangeInclusive;
+use std::{ops::RangeInclusive, time::Duration};
 
 use chrono::{DateTime, NaiveDate, Utc};
+use futures::StreamExt;
 use monero::{Address, Network};
 use monero_rpc::{
-    BlockHash, BlockHeaderResponse, BlockTemplate, GenerateBlocksResponse, HashString,
+    BlockEvent, BlockHash, BlockHeaderResponse, BlockTemplate, GenerateBlocksResponse, HashString,
     RegtestDaemonJsonRpcClient,
 };
 use serde::Deserialize;
@@ -16,6 +17,14 @@ pub async fn get_block_count_assert_height(
     assert_eq!(count.get(), expected_height);
 }
 
+pub async fn get_block_count_error_wrong_network(regtest: &RegtestDaemonJsonRpcClient) {
+    let err = regtest.get_block_count().await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Client bound to Stagenet, but the daemon is on Mainnet"
+    );
+}
+
 pub async fn on_get_block_hash_assert_hash(
     regtest: &RegtestDaemonJsonRpcClient,
     height: u64,
@@ -332,6 +341,44 @@ pub async fn get_block_headers_range_assert_block_headers(
     assert_eq!(block_headers, expected_block_headers);
 }
 
+pub async fn get_block_headers_range_chunked_assert_block_headers(
+    regtest: &RegtestDaemonJsonRpcClient,
+    range: RangeInclusive<u64>,
+    chunk_size: u64,
+    concurrency: usize,
+) {
+    let expected_block_headers = regtest
+        .get_block_headers_range(range.clone())
+        .await
+        .unwrap();
+    let block_headers = regtest
+        .get_block_headers_range_chunked(range, chunk_size, concurrency)
+        .await
+        .unwrap();
+    assert_eq!(block_headers, expected_block_headers);
+}
+
+pub async fn block_stream_assert_blocks(
+    regtest: &RegtestDaemonJsonRpcClient,
+    range: RangeInclusive<u64>,
+) {
+    let (expected_block_headers, _) = regtest
+        .get_block_headers_range(range.clone())
+        .await
+        .unwrap();
+    let events: Vec<BlockEvent> = regtest
+        .block_stream(Some(*range.start()), Duration::from_millis(100))
+        .take(expected_block_headers.len())
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    let expected_events: Vec<BlockEvent> = expected_block_headers
+        .into_iter()
+        .map(BlockEvent::Block)
+        .collect();
+    assert_eq!(events, expected_events);
+}
+
 pub async fn get_block_headers_range_error(
     regtest: &RegtestDaemonJsonRpcClient,
     range: RangeInclusive<u64>,
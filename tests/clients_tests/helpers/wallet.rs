@@ -1,14 +1,25 @@
 use std::collections::HashMap;
 use std::ops::Range;
+use std::time::Duration;
+
+use futures::StreamExt;
 
 use monero::{
-    cryptonote::subaddress::Index, util::address::PaymentId, Address, Amount, Hash, PrivateKey,
+    cryptonote::subaddress::Index, util::address::PaymentId, Address, Amount, Hash, Network,
+    PrivateKey, Transaction, TxIn,
 };
 use monero_rpc::{
-    AddressData, BalanceData, GenerateFromKeysArgs, GetAccountsData, GetTransfersCategory,
-    GetTransfersSelector, GotTransfer, IncomingTransfers, KeyImageImportResponse, Payment,
-    PrivateKeyType, SignedKeyImage, SignedTransferOutput, SweepAllArgs, TransferData,
-    TransferOptions, TransferPriority, TransferType, WalletClient, WalletCreation,
+    integrated_address as integrated_address_offline,
+    split_integrated_address as split_integrated_address_offline,
+    validate_address as validate_address_offline, verify_signature, AccountTag, AddressBookEntry,
+    AddressData, AddressValidation, BalanceData, GenerateFromKeysArgs, GetAccountsData,
+    GetTransfersCategory, GetTransfersSelector, GotTransfer, HashString, InMemoryInvoiceStore,
+    IncomingTransfers, InvoiceStatus, InvoiceStore, KeyImage, KeyImageImportResponse,
+    MultisigCoordinator, MultisigStatus, Payment, PaymentProcessor, PrivateKeyType, ReserveProof,
+    ReserveProofAmount, ReserveProofCheck, ReserveProofStatus, SignatureType, SignedKeyImage,
+    SignedTransferOutput, SignedTxSet, SweepAllArgs, SweepOptions, SweepSingleArgs, TransferData,
+    TransferEvent, TransferOptions, TransferPriority, TransferSplitData, TransferType, TxKey,
+    TxKeyCheck, TxMetadata, TxProofCheck, UnsignedTxSet, WalletClient, WalletCreation,
 };
 
 fn get_random_name() -> String {
@@ -316,6 +327,274 @@ pub async fn get_accounts_error_unregistered_tag(wallet: &WalletClient, tag: Str
     );
 }
 
+pub async fn create_account_assert_address_and_account_index(
+    wallet: &WalletClient,
+    label: Option<String>,
+    expected_address_and_account_index: (Address, u32),
+) {
+    let account_created = wallet.create_account(label).await.unwrap();
+    assert_eq!(account_created, expected_address_and_account_index);
+}
+
+pub async fn label_account_assert_ok(wallet: &WalletClient, account_index: u32, label: String) {
+    wallet.label_account(account_index, label).await.unwrap()
+}
+
+pub async fn label_account_error_invalid_account_index(wallet: &WalletClient, account_index: u32) {
+    let err = wallet
+        .label_account(account_index, "".to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Server error: account index is out of bound"
+    );
+}
+
+pub async fn tag_accounts_assert_ok(wallet: &WalletClient, tag: String, accounts: Vec<u32>) {
+    wallet.tag_accounts(tag, accounts).await.unwrap()
+}
+
+pub async fn untag_accounts_assert_ok(wallet: &WalletClient, accounts: Vec<u32>) {
+    wallet.untag_accounts(accounts).await.unwrap()
+}
+
+pub async fn set_account_tag_description_assert_ok(
+    wallet: &WalletClient,
+    tag: String,
+    description: String,
+) {
+    wallet
+        .set_account_tag_description(tag, description)
+        .await
+        .unwrap()
+}
+
+pub async fn get_account_tags_assert_account_tags(
+    wallet: &WalletClient,
+    expected_account_tags: Vec<AccountTag>,
+) {
+    let account_tags = wallet.get_account_tags().await.unwrap();
+    assert_eq!(account_tags, expected_account_tags);
+}
+
+pub async fn add_address_book_assert_index(
+    wallet: &WalletClient,
+    address: Address,
+    description: Option<String>,
+    expected_index: u64,
+) {
+    let index = wallet.add_address_book(address, description).await.unwrap();
+    assert_eq!(index, expected_index);
+}
+
+pub async fn edit_address_book_assert_ok(
+    wallet: &WalletClient,
+    index: u64,
+    address: Option<Address>,
+    description: Option<String>,
+) {
+    wallet
+        .edit_address_book(index, address, description)
+        .await
+        .unwrap()
+}
+
+pub async fn delete_address_book_assert_ok(wallet: &WalletClient, index: u64) {
+    wallet.delete_address_book(index).await.unwrap()
+}
+
+pub async fn get_address_book_assert_entries(
+    wallet: &WalletClient,
+    entries: Vec<u64>,
+    expected_entries: Vec<AddressBookEntry>,
+) {
+    let address_book = wallet.get_address_book(entries).await.unwrap();
+    assert_eq!(address_book, expected_entries);
+}
+
+pub async fn get_address_book_error_invalid_index(wallet: &WalletClient, index: u64) {
+    let err = wallet.get_address_book(vec![index]).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Server error: Index out of range: {index}")
+    );
+}
+
+pub async fn make_integrated_address_assert_offline_address(
+    wallet: &WalletClient,
+    standard_address: Address,
+    payment_id: PaymentId,
+) {
+    let (integrated_address, res_payment_id) = wallet
+        .make_integrated_address(Some(standard_address), Some(payment_id))
+        .await
+        .unwrap();
+    assert_eq!(res_payment_id, payment_id);
+    assert_eq!(
+        integrated_address,
+        integrated_address_offline(&standard_address, payment_id).unwrap()
+    );
+
+    let parts = wallet
+        .split_integrated_address(integrated_address)
+        .await
+        .unwrap();
+    assert_eq!(
+        parts,
+        split_integrated_address_offline(&integrated_address).unwrap()
+    );
+    assert_eq!(parts.standard_address, standard_address);
+}
+
+pub async fn make_integrated_address_random_payment_id_assert_ok(
+    wallet: &WalletClient,
+    expected_standard_address: Address,
+) {
+    let (integrated_address, payment_id) =
+        wallet.make_integrated_address(None, None).await.unwrap();
+    let parts = split_integrated_address_offline(&integrated_address).unwrap();
+    assert_eq!(parts.standard_address, expected_standard_address);
+    assert_eq!(parts.payment_id, payment_id);
+}
+
+pub async fn split_integrated_address_error_not_integrated(
+    wallet: &WalletClient,
+    address: Address,
+) {
+    let err = wallet.split_integrated_address(address).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Server error: Address is not an integrated address"
+    );
+}
+
+pub async fn validate_address_assert_offline_validation(wallet: &WalletClient, address: Address) {
+    for any_net_type in [false, true] {
+        let validation = wallet
+            .validate_address(address.to_string(), Some(any_net_type), Some(false))
+            .await
+            .unwrap();
+        assert_eq!(
+            validation,
+            validate_address_offline(&address.to_string(), Network::Mainnet, any_net_type)
+        );
+    }
+}
+
+pub async fn validate_address_assert_invalid(wallet: &WalletClient, address: String) {
+    let validation = wallet
+        .validate_address(address, Some(true), Some(false))
+        .await
+        .unwrap();
+    assert_eq!(
+        validation,
+        AddressValidation {
+            valid: false,
+            integrated: false,
+            subaddress: false,
+            nettype: None,
+            openalias_address: None,
+        }
+    );
+}
+
+pub async fn with_network_assert_ok(expected_address: Address) {
+    let wallet = super::setup_wallet_with_network(Network::Mainnet);
+    let address = wallet.get_address(0, None).await.unwrap().address;
+    assert_eq!(address, expected_address);
+}
+
+pub async fn with_network_error_wrong_network() {
+    let wallet = super::setup_wallet_with_network(Network::Testnet);
+    let err = wallet.get_balance(0, None).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Client bound to Testnet, but the wallet is on Mainnet"
+    );
+}
+
+pub async fn is_multisig_assert_not_multisig(wallet: &WalletClient) {
+    let status = wallet.is_multisig().await.unwrap();
+    assert_eq!(
+        status,
+        MultisigStatus {
+            multisig: false,
+            ready: false,
+            threshold: 0,
+            total: 0,
+        }
+    );
+}
+
+pub async fn export_multisig_info_error_not_multisig(wallet: &WalletClient) {
+    let err = wallet.export_multisig_info().await.unwrap_err();
+    assert_eq!(err.to_string(), "Server error: This wallet is not multisig");
+}
+
+pub async fn multisig_setup_assert_ready(
+    coordinator: &MultisigCoordinator,
+    password: &str,
+) -> Address {
+    let address = coordinator.setup(password.to_string(), true).await.unwrap();
+
+    for wallet in coordinator.wallets() {
+        let status = wallet.is_multisig().await.unwrap();
+        assert_eq!(
+            status,
+            MultisigStatus {
+                multisig: true,
+                ready: true,
+                threshold: coordinator.threshold(),
+                total: coordinator.wallets().len() as u32,
+            }
+        );
+        let address_data = wallet.get_address(0, None).await.unwrap();
+        assert_eq!(address_data.address, address);
+    }
+
+    address
+}
+
+pub async fn multisig_transfer_assert_ok(
+    coordinator: &MultisigCoordinator,
+    signers: &[usize],
+    destination: Address,
+    amount: Amount,
+) -> Hash {
+    let tx_hashes = coordinator
+        .transfer(
+            signers,
+            vec![(destination, amount)],
+            TransferPriority::Default,
+            TransferOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(tx_hashes.len(), 1);
+
+    // the spend changed the outputs of the multisig wallet, so the next one needs a sync
+    coordinator.sync().await.unwrap();
+
+    tx_hashes[0]
+}
+
+pub async fn multisig_transfer_error_not_enough_signers(coordinator: &MultisigCoordinator) {
+    let err = coordinator
+        .transfer(
+            &[0],
+            vec![],
+            TransferPriority::Default,
+            TransferOptions::default(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Expected {} signers, got 1", coordinator.threshold())
+    );
+}
+
 pub async fn get_height_assert_height(wallet: &WalletClient, expected_height: u64) {
     let height = wallet.get_height().await.unwrap();
     assert_eq!(height.get(), expected_height);
@@ -368,7 +647,7 @@ pub async fn get_balance_assert_balance_data(
 
 pub async fn transfer_assert_ok(
     wallet: &WalletClient,
-    destinations: HashMap<Address, Amount>,
+    destinations: Vec<(Address, Amount)>,
     options: TransferOptions,
     priority: TransferPriority,
 ) -> TransferData {
@@ -376,14 +655,47 @@ pub async fn transfer_assert_ok(
         .transfer(destinations.clone(), priority, options)
         .await
         .unwrap();
-    let dest_amount = Amount::from_pico(destinations.into_values().map(|a| a.as_pico()).sum());
+    let dest_amount = Amount::from_pico(destinations.iter().map(|(_, a)| a.as_pico()).sum());
     assert_eq!(t.amount, dest_amount);
     t
 }
 
+pub async fn transfer_assert_fee_subtracted(
+    wallet: &WalletClient,
+    destinations: Vec<(Address, Amount)>,
+) {
+    let version = wallet.get_version().await.unwrap();
+    let t = wallet
+        .transfer(
+            destinations.clone(),
+            TransferPriority::Default,
+            TransferOptions {
+                do_not_relay: Some(true),
+                subtract_fee_from_outputs: Some(vec![0]),
+                get_tx_hex: Some(false),
+                get_tx_metadata: Some(false),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let dest_amount = Amount::from_pico(destinations.iter().map(|(_, a)| a.as_pico()).sum());
+    assert!(t.fee > Amount::ZERO);
+    // `subtract_fee_from_outputs` is ignored before wallet RPC v1.26 (v0.18.2.0)
+    if version.1 >= 26 {
+        assert_eq!(t.amount + t.fee, dest_amount);
+    } else {
+        assert_eq!(t.amount, dest_amount);
+    }
+    assert!(t.tx_blob.0.is_empty());
+    assert!(t.tx_metadata.0.is_empty());
+    assert!(!t.tx_key.0.is_empty());
+}
+
 pub async fn transfer_error_invalid_balance(
     wallet: &WalletClient,
-    destinations: HashMap<Address, Amount>,
+    destinations: Vec<(Address, Amount)>,
     options: TransferOptions,
 ) {
     let err = wallet
@@ -395,7 +707,7 @@ pub async fn transfer_error_invalid_balance(
 
 pub async fn transfer_error_invalid_address(
     wallet: &WalletClient,
-    destinations: HashMap<Address, Amount>,
+    destinations: Vec<(Address, Amount)>,
     options: TransferOptions,
     wrong_address: Address,
 ) {
@@ -412,9 +724,29 @@ pub async fn transfer_error_invalid_address(
     );
 }
 
+pub async fn transfer_error_wrong_network(
+    destinations: Vec<(Address, Amount)>,
+    options: TransferOptions,
+    wrong_address: Address,
+) {
+    // rejected before calling the wallet-rpc
+    let wallet = super::setup_wallet_with_network(Network::Mainnet);
+    let err = wallet
+        .transfer(destinations, TransferPriority::Default, options)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "{} is a Testnet address, expected a Mainnet address",
+            wrong_address
+        )
+    );
+}
+
 pub async fn transfer_error_payment_id_obsolete(
     wallet: &WalletClient,
-    destinations: HashMap<Address, Amount>,
+    destinations: Vec<(Address, Amount)>,
     options: TransferOptions,
 ) {
     let err = wallet
@@ -424,22 +756,41 @@ pub async fn transfer_error_payment_id_obsolete(
     assert_eq!(err.to_string(), "Server error: Standalone payment IDs are obsolete. Use subaddresses or integrated addresses instead");
 }
 
+pub async fn transfer_split_assert_ok(
+    wallet: &WalletClient,
+    destinations: Vec<(Address, Amount)>,
+    options: TransferOptions,
+) -> TransferSplitData {
+    let t = wallet
+        .transfer_split(destinations.clone(), TransferPriority::Default, options)
+        .await
+        .unwrap();
+    let dest_amount: u64 = destinations.iter().map(|(_, a)| a.as_pico()).sum();
+    assert_eq!(
+        t.amount_list.iter().map(|a| a.as_pico()).sum::<u64>(),
+        dest_amount
+    );
+    let tx_count = t.tx_hash_list.len();
+    assert!(tx_count > 0);
+    assert_eq!(t.tx_key_list.len(), tx_count);
+    assert_eq!(t.fee_list.len(), tx_count);
+    assert_eq!(t.weight_list.len(), tx_count);
+    assert_eq!(t.tx_blob_list.len(), tx_count);
+    assert_eq!(t.tx_metadata_list.len(), tx_count);
+    t
+}
+
 pub async fn relay_tx_assert_tx_hash(
     wallet: &WalletClient,
-    tx_metadata_hex: String,
+    tx_metadata: TxMetadata,
     expected_tx_hash: String,
 ) {
-    let res = wallet.relay_tx(tx_metadata_hex).await.unwrap();
+    let res = wallet.relay_tx(tx_metadata).await.unwrap();
     assert_eq!(res.to_string(), expected_tx_hash);
 }
 
-pub async fn relay_tx_error_invalid_hex(wallet: &WalletClient, tx_metadata_hex: String) {
-    let err = wallet.relay_tx(tx_metadata_hex).await.unwrap_err();
-    assert_eq!(err.to_string(), "Server error: Failed to parse hex.");
-}
-
-pub async fn relay_tx_error_invalid_tx_metadata(wallet: &WalletClient, tx_metadata_hex: String) {
-    let err = wallet.relay_tx(tx_metadata_hex).await.unwrap_err();
+pub async fn relay_tx_error_invalid_tx_metadata(wallet: &WalletClient, tx_metadata: TxMetadata) {
+    let err = wallet.relay_tx(tx_metadata).await.unwrap_err();
     assert_eq!(
         err.to_string(),
         "Server error: Failed to parse tx metadata."
@@ -481,21 +832,78 @@ pub async fn get_transfer_error_invalid_account_index(
 pub async fn check_tx_key_assert_confirmations_in_pool_status_received_amount(
     wallet: &WalletClient,
     txid: Hash,
-    tx_key: Vec<u8>,
+    tx_key: TxKey,
     address: Address,
-    expected_confirmations_and_in_pool_status_and_received_amount: (u64, bool, Amount),
+    expected_tx_key_check: TxKeyCheck,
 ) {
     let res = wallet.check_tx_key(txid, tx_key, address).await.unwrap();
-    assert_eq!(
-        res,
-        expected_confirmations_and_in_pool_status_and_received_amount
-    );
+    assert_eq!(res, expected_tx_key_check);
+}
+
+pub async fn get_tx_key_assert_tx_key(wallet: &WalletClient, txid: Hash, expected_tx_key: TxKey) {
+    let tx_key = wallet.get_tx_key(txid).await.unwrap();
+    assert_eq!(tx_key, expected_tx_key);
+}
+
+pub async fn get_tx_key_error_invalid_txid(wallet: &WalletClient, txid: Hash) {
+    let err = wallet.get_tx_key(txid).await.unwrap_err();
+    assert_eq!(err.to_string(), "Server error: Failed to get tx key");
+}
+
+pub async fn tx_proof_assert_check(
+    wallet: &WalletClient,
+    txid: Hash,
+    address: Address,
+    message: Option<String>,
+    expected_tx_proof_check: TxProofCheck,
+) {
+    let signature = wallet
+        .get_tx_proof(txid, address, message.clone())
+        .await
+        .unwrap();
+    assert!(signature.starts_with("OutProofV2") || signature.starts_with("InProofV2"));
+
+    let res = wallet
+        .check_tx_proof(txid, address, message.clone(), signature.clone())
+        .await
+        .unwrap();
+    assert_eq!(res, expected_tx_proof_check);
+
+    // the signature does not hold for another message
+    let res = wallet
+        .check_tx_proof(
+            txid,
+            address,
+            Some("another message".to_string()),
+            signature,
+        )
+        .await
+        .unwrap();
+    assert!(!res.good);
+}
+
+pub async fn spend_proof_assert_good(wallet: &WalletClient, txid: Hash, message: Option<String>) {
+    let signature = wallet.get_spend_proof(txid, message.clone()).await.unwrap();
+    assert!(signature.starts_with("SpendProofV1"));
+
+    let good = wallet
+        .check_spend_proof(txid, message, signature.clone())
+        .await
+        .unwrap();
+    assert!(good);
+
+    // the signature does not hold for another message
+    let good = wallet
+        .check_spend_proof(txid, Some("another message".to_string()), signature)
+        .await
+        .unwrap();
+    assert!(!good);
 }
 
 pub async fn check_tx_key_error_invalid_txid(
     wallet: &WalletClient,
     txid: Hash,
-    tx_key: Vec<u8>,
+    tx_key: TxKey,
     address: Address,
 ) {
     let err = wallet
@@ -511,7 +919,7 @@ pub async fn check_tx_key_error_invalid_txid(
 pub async fn check_tx_key_error_invalid_tx_key(
     wallet: &WalletClient,
     txid: Hash,
-    tx_key: Vec<u8>,
+    tx_key: TxKey,
     address: Address,
 ) {
     let err = wallet
@@ -524,7 +932,7 @@ pub async fn check_tx_key_error_invalid_tx_key(
 pub async fn check_tx_key_error_invalid_address(
     wallet: &WalletClient,
     txid: Hash,
-    tx_key: Vec<u8>,
+    tx_key: TxKey,
     address: Address,
 ) {
     let err = wallet
@@ -534,6 +942,110 @@ pub async fn check_tx_key_error_invalid_address(
     assert_eq!(err.to_string(), "Server error: Invalid address");
 }
 
+pub async fn reserve_proof_assert_check(
+    wallet: &WalletClient,
+    amount: ReserveProofAmount,
+    address: Address,
+    expected_total: Amount,
+) -> ReserveProof {
+    let message = Some("proof of reserves".to_string());
+    let signature = wallet
+        .get_reserve_proof(amount, message.clone())
+        .await
+        .unwrap();
+    assert!(signature.starts_with("ReserveProofV2"));
+
+    let res = wallet
+        .check_reserve_proof(address, message.clone(), signature.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        res,
+        ReserveProofCheck {
+            good: true,
+            total: expected_total,
+            spent: Amount::ZERO,
+        }
+    );
+
+    ReserveProof {
+        address,
+        message,
+        signature,
+    }
+}
+
+pub async fn audit_reserve_proofs_assert_reserves(
+    wallet: &WalletClient,
+    proof: ReserveProof,
+    expected_reserves: Amount,
+) {
+    let audit = wallet
+        .audit_reserve_proofs(vec![proof.clone()])
+        .await
+        .unwrap();
+    assert!(audit.is_valid());
+    assert_eq!(audit.reserves, expected_reserves);
+
+    // the signature does not hold for another message, and a second proof of the same address is
+    // not counted
+    let wrong_message_proof = ReserveProof {
+        message: Some("another message".to_string()),
+        ..proof.clone()
+    };
+    let audit = wallet
+        .audit_reserve_proofs(vec![wrong_message_proof, proof.clone(), proof])
+        .await
+        .unwrap();
+    assert!(!audit.is_valid());
+    assert_eq!(audit.reserves, expected_reserves);
+    assert_eq!(audit.proofs[0].1, ReserveProofStatus::Invalid);
+    assert_eq!(audit.proofs[2].1, ReserveProofStatus::Duplicate);
+}
+
+pub async fn sign_assert_verify(
+    wallet: &WalletClient,
+    data: &str,
+    index: Index,
+    address: Address,
+    signature_type: SignatureType,
+) {
+    // `signature_type` and subaddress signatures need v0.18 (wallet RPC v1.25)
+    let is_v0_18 = wallet.get_version().await.unwrap().1 >= 25;
+    if !is_v0_18 && index != (Index { major: 0, minor: 0 }) {
+        return;
+    }
+
+    let signature = wallet
+        .sign(data.to_string(), index, signature_type)
+        .await
+        .unwrap();
+
+    let res = wallet
+        .verify(data.to_string(), address, signature.clone())
+        .await
+        .unwrap();
+    assert!(res.good);
+    if is_v0_18 {
+        assert_eq!(res.signature_type, Some(signature_type));
+    }
+
+    // the same signature is valid offline, but not for other data
+    let offline_res = verify_signature(data, &address, &signature);
+    assert!(offline_res.good);
+    assert_eq!(offline_res.version, res.version);
+    if is_v0_18 {
+        assert_eq!(offline_res.signature_type, Some(signature_type));
+    }
+    assert!(!verify_signature("other data", &address, &signature).good);
+
+    let res = wallet
+        .verify("other data".to_string(), address, signature)
+        .await
+        .unwrap();
+    assert!(!res.good);
+}
+
 pub async fn export_key_images_assert_ok(
     wallet: &WalletClient,
     all: Option<bool>,
@@ -594,7 +1106,7 @@ pub async fn incoming_transfers_assert_incoming_transfers(
         .unwrap();
 
     // we will not test agains `IncomingTransfer.global_index` nor `IncomingTransfer.tx_size` nor
-    // `IncomingTransfer::key_image`
+    // `IncomingTransfer::key_image` nor `IncomingTransfer::unlocked`
     if let Some(ref transfers) = incoming_transfers.transfers {
         expected_incoming_transfers
             .transfers
@@ -606,6 +1118,7 @@ pub async fn incoming_transfers_assert_incoming_transfers(
                 t.global_index = transfers[i].global_index;
                 t.tx_size = transfers[i].tx_size;
                 t.key_image = transfers[i].key_image.clone();
+                t.unlocked = transfers[i].unlocked;
             });
     }
 
@@ -614,27 +1127,27 @@ pub async fn incoming_transfers_assert_incoming_transfers(
 
 pub async fn sign_transfer_assert_ok(
     wallet: &WalletClient,
-    unsigned_txset: Vec<u8>,
+    unsigned_txset: UnsignedTxSet,
 ) -> SignedTransferOutput {
     let res = wallet.sign_transfer(unsigned_txset).await.unwrap();
-    assert!(!res.signed_txset.is_empty());
+    assert!(!res.signed_txset.0.is_empty());
     assert!(!res.tx_hash_list.is_empty());
     assert!(!res.tx_raw_list.is_empty());
     res
 }
 
-pub async fn sign_transfer_error_cannot_load(wallet: &WalletClient, unsigned_txset: Vec<u8>) {
+pub async fn sign_transfer_error_cannot_load(wallet: &WalletClient, unsigned_txset: UnsignedTxSet) {
     let err = wallet.sign_transfer(unsigned_txset).await.unwrap_err();
     assert_eq!(err.to_string(), "Server error: cannot load unsigned_txset");
 }
 
-pub async fn submit_transfer_assert_ok(wallet: &WalletClient, tx_data_hex: Vec<u8>) {
-    let res = wallet.submit_transfer(tx_data_hex).await.unwrap();
+pub async fn submit_transfer_assert_ok(wallet: &WalletClient, signed_txset: SignedTxSet) {
+    let res = wallet.submit_transfer(signed_txset).await.unwrap();
     assert!(!res.is_empty());
 }
 
-pub async fn submit_transfer_error_parse(wallet: &WalletClient, tx_data_hex: Vec<u8>) {
-    let err = wallet.submit_transfer(tx_data_hex).await.unwrap_err();
+pub async fn submit_transfer_error_parse(wallet: &WalletClient, signed_txset: SignedTxSet) {
+    let err = wallet.submit_transfer(signed_txset).await.unwrap_err();
     assert_eq!(
         err.to_string(),
         "Server error: Failed to parse signed tx data."
@@ -701,26 +1214,26 @@ pub async fn sweep_all_assert_ok(wallet: &WalletClient, args: SweepAllArgs) {
     assert!(!res.amount_list.is_empty());
     assert!(!res.fee_list.is_empty());
 
-    assert_eq!(res.multisig_txset, "");
-    assert_eq!(res.unsigned_txset, "");
+    assert!(res.multisig_txset.0.is_empty());
+    assert!(res.unsigned_txset.0.is_empty());
 
-    if let Some(true) = args.get_tx_keys {
-        assert!(!res.tx_key_list.unwrap().is_empty());
-    } else {
-        assert!(res.tx_key_list.is_none());
-    }
-
-    if let Some(true) = args.get_tx_metadata {
-        assert!(!res.tx_metadata_list.unwrap().is_empty());
-    } else {
-        assert!(res.tx_metadata_list.is_none());
-    }
-
-    if let Some(true) = args.get_tx_hex {
-        assert!(!res.tx_blob_list.unwrap().is_empty());
-    } else {
-        assert!(res.tx_blob_list.is_none());
-    }
+    let tx_count = res.tx_hash_list.len();
+    let expected_len = |requested: Option<bool>| match requested {
+        Some(true) => tx_count,
+        _ => 0,
+    };
+    assert_eq!(
+        res.tx_key_list.len(),
+        expected_len(args.options.get_tx_keys)
+    );
+    assert_eq!(
+        res.tx_metadata_list.len(),
+        expected_len(args.options.get_tx_metadata)
+    );
+    assert_eq!(
+        res.tx_blob_list.len(),
+        expected_len(args.options.get_tx_hex)
+    );
 }
 
 pub async fn sweep_all_error_no_unlocked_balance(wallet: &WalletClient, args: SweepAllArgs) {
@@ -730,3 +1243,144 @@ pub async fn sweep_all_error_no_unlocked_balance(wallet: &WalletClient, args: Sw
         "Server error: No unlocked balance in the specified account"
     );
 }
+
+pub async fn transfer_stream_assert_outgoing(wallet: &WalletClient, txid: Hash) {
+    let events = wallet.transfer_stream(None, None, 10, Duration::from_millis(100));
+    futures::pin_mut!(events);
+
+    // the pending transfers are yielded by the first poll
+    let found = tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(event) = events.next().await {
+            if let TransferEvent::Outgoing(transfer) = event.unwrap() {
+                if transfer.txid.0 == txid.as_bytes() {
+                    return transfer;
+                }
+            }
+        }
+        unreachable!("the transfer stream never ends");
+    })
+    .await
+    .unwrap();
+    assert_eq!(found.transfer_type, GetTransfersCategory::Pending);
+}
+
+pub async fn payment_processor_create_invoice_assert_unpaid(wallet: &WalletClient) {
+    let processor = PaymentProcessor::new(
+        wallet.clone(),
+        InMemoryInvoiceStore::default(),
+        0,
+        1,
+        chrono::Duration::hours(1),
+    );
+    let id = get_random_name();
+    let invoice = processor
+        .create_invoice(
+            id.clone(),
+            Amount::from_pico(1000000000000),
+            chrono::Duration::hours(1),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        wallet.get_address_index(invoice.address).await.unwrap(),
+        invoice.subaddr_index
+    );
+    assert_eq!(invoice.status, InvoiceStatus::Unpaid);
+
+    // nothing was paid to the new subaddress
+    assert!(processor.poll().await.unwrap().is_empty());
+    assert_eq!(processor.store().get(&id).await.unwrap(), Some(invoice));
+
+    let err = processor
+        .create_invoice(
+            id.clone(),
+            Amount::from_pico(1000000000000),
+            chrono::Duration::hours(1),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), format!("Invoice {} already exists", id));
+}
+
+pub async fn sweep_single_error_unknown_key_image(wallet: &WalletClient, address: Address) {
+    let err = wallet
+        .sweep_single(SweepSingleArgs {
+            address,
+            key_image: HashString(KeyImage::repeat_byte(1)),
+            priority: TransferPriority::Default,
+            ring_size: 16,
+            unlock_time: 0,
+            outputs: None,
+            options: SweepOptions::default(),
+        })
+        .await
+        .unwrap_err();
+    // the message depends on the wallet version
+    assert!(err.to_string().starts_with("Server error: "));
+}
+
+pub async fn freeze_thaw_assert_frozen(wallet: &WalletClient) {
+    let transfers = wallet
+        .incoming_transfers(TransferType::Available, Some(0), None)
+        .await
+        .unwrap()
+        .transfers
+        .unwrap();
+    let key_image = transfers[0].key_image.as_ref().unwrap().0;
+
+    assert!(!wallet.frozen(key_image).await.unwrap());
+    wallet.freeze(key_image).await.unwrap();
+    assert!(wallet.frozen(key_image).await.unwrap());
+    wallet.thaw(key_image).await.unwrap();
+    assert!(!wallet.frozen(key_image).await.unwrap());
+}
+
+pub async fn transfer_from_outputs_assert_others_thawed(
+    wallet: &WalletClient,
+    destination: Address,
+) {
+    let transfers = wallet
+        .incoming_transfers(TransferType::Available, Some(0), None)
+        .await
+        .unwrap()
+        .transfers
+        .unwrap();
+    assert!(transfers.len() > 1);
+    let chosen = transfers.iter().max_by_key(|t| t.amount).unwrap();
+    let chosen_key_image = chosen.key_image.as_ref().unwrap().0;
+
+    let transfer_data = wallet
+        .transfer_from_outputs(
+            &[chosen_key_image],
+            vec![(destination, Amount::from_xmr(1.0).unwrap())],
+            TransferPriority::Default,
+            TransferOptions {
+                account_index: Some(0),
+                do_not_relay: Some(true),
+                get_tx_hex: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    // the transaction only spends the chosen output...
+    let tx: Transaction = monero::consensus::deserialize(&transfer_data.tx_blob.0 .0).unwrap();
+    let spent_key_images = tx
+        .prefix
+        .inputs
+        .iter()
+        .map(|input| match input {
+            TxIn::ToKey { k_image, .. } => k_image.image.to_fixed_bytes(),
+            TxIn::Gen { .. } => panic!("unexpected coinbase input"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(spent_key_images, vec![chosen_key_image.to_fixed_bytes()]);
+
+    // ... and the others are thawed afterwards
+    for transfer in &transfers {
+        let key_image = transfer.key_image.as_ref().unwrap().0;
+        assert!(!wallet.frozen(key_image).await.unwrap());
+    }
+}
@@ -4,4 +4,5 @@ pub mod all_clients_interaction;
 pub mod basic_daemon_rpc;
 pub mod basic_wallet;
 pub mod empty_blockchain;
+pub mod multisig;
 pub mod non_empty_blockchain;
@@ -0,0 +1,58 @@
+use monero::{Address, Amount, Network};
+use monero_rpc::MultisigCoordinator;
+
+use super::helpers;
+
+/*
+* The purpose of this test is to run a 2-of-3 multisig wallet with `MultisigCoordinator`, whose
+* participants are three `monero-wallet-rpc` instances.
+*
+* It runs after `all_clients_interaction`, since it needs blocks with outputs to use as decoys,
+* and since it uses the wallet of the other tests as its first participant.
+*/
+
+pub async fn run() {
+    let (regtest, _, _) = helpers::setup_monero();
+    let wallets = helpers::setup_multisig_wallets();
+
+    // STEP 1: every participant creates a new wallet, then the coordinator runs the key exchange.
+    for wallet in &wallets {
+        helpers::wallet::create_wallet_with_password_assert_ok(wallet, helpers::PWD_1).await;
+        helpers::wallet::is_multisig_assert_not_multisig(wallet).await;
+    }
+
+    let coordinator = MultisigCoordinator::new(wallets, 2).unwrap();
+    let multisig_address =
+        helpers::wallet::multisig_setup_assert_ready(&coordinator, helpers::PWD_1).await;
+
+    // STEP 2: the multisig wallet gets funds from a coinbase, which unlocks after 60 blocks.
+    let other_address = Address::from_keypair(Network::Mainnet, &helpers::get_keypair_3());
+    helpers::regtest::generate_blocks_assert_ok(&regtest, 1, multisig_address).await;
+    helpers::regtest::generate_blocks_assert_ok(&regtest, 60, other_address).await;
+    for wallet in coordinator.wallets() {
+        helpers::wallet::refresh_assert_received_money(wallet, None, true).await;
+    }
+
+    // STEP 3: two of the three participants spend, then the third one and the first one spend
+    // what is left.
+    helpers::wallet::multisig_transfer_error_not_enough_signers(&coordinator).await;
+    helpers::wallet::multisig_transfer_assert_ok(
+        &coordinator,
+        &[0, 2],
+        other_address,
+        Amount::from_xmr(1.0).unwrap(),
+    )
+    .await;
+
+    helpers::regtest::generate_blocks_assert_ok(&regtest, 10, other_address).await;
+    for wallet in coordinator.wallets() {
+        helpers::wallet::refresh_assert_received_money(wallet, None, true).await;
+    }
+    helpers::wallet::multisig_transfer_assert_ok(
+        &coordinator,
+        &[2, 0],
+        other_address,
+        Amount::from_xmr(1.0).unwrap(),
+    )
+    .await;
+}
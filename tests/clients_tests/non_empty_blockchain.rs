@@ -119,6 +119,14 @@ pub async fn run() {
         vec![last_but_one_block_header, last_block_header],
     )
     .await;
+    helpers::regtest::get_block_headers_range_chunked_assert_block_headers(
+        &regtest,
+        0..=current_top_block_height,
+        7,
+        3,
+    )
+    .await;
+    helpers::regtest::block_stream_assert_blocks(&regtest, 50..=current_top_block_height).await;
 
     // STEP 3: we test the last function that can modify the blockchain state: `submit_block`.
     // In order for it to work, we just get a block template on which to mine. Since the difficulty
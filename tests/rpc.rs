@@ -42,6 +42,9 @@ async fn main_functional_test() {
      *    run last because the other tests test each client individually, but `all_clients_interaction`
      *    calls functions from all clients.
      *
+     *    `multisig` runs after it, since it needs the outputs created by `all_clients_interaction`
+     *    as decoys, and it also uses the `monero-wallet-rpc` of the other tests.
+     *
      */
 
     let handle1 = tokio::spawn(clients_tests::basic_wallet::run());
@@ -57,4 +60,5 @@ async fn main_functional_test() {
     res.unwrap();
 
     clients_tests::all_clients_interaction::run().await;
+    clients_tests::multisig::run().await;
 }